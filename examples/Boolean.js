@@ -0,0 +1,9 @@
+var t = new Boolean(true);
+var f = new Boolean(false);
+
+print(Boolean(0), Boolean(1), Boolean(""), Boolean("js"));
+print(true.toString());
+print(t.valueOf(), f.valueOf());
+print(t + " " + f);
+print(f.toString());
+print(Boolean.prototype.valueOf.call(true), Boolean.prototype.toString.call(false));
@@ -0,0 +1,37 @@
+var d = new Date(2016, 2, 1, 10, 30, 15, 250);
+print(d.toString());
+print(d.toUTCString());
+print(d.toISOString());
+print(d.getFullYear(), d.getMonth(), d.getDate(), d.getDay());
+print(d.getHours(), d.getMinutes(), d.getSeconds(), d.getMilliseconds());
+print(d.getTimezoneOffset());
+
+print(Date.UTC(1970, 0, 1), Date.UTC(2000, 1, 29, 12));
+print(Date.parse("2000-02-29T12:00:00.000Z"), Date.parse("2000-02-29"));
+print(Date.parse(d.toString()) - d.getTime(), Date.parse(d.toUTCString()) - d.getTime());
+print(Date.parse("not a date"));
+
+d.setMonth(11, 31);
+print(d.toISOString());
+d.setHours(23, 59, 59, 999);
+d.setMilliseconds(1000);
+print(d.toISOString());
+d.setFullYear(1999);
+print(d.toISOString(), d.getYear());
+d.setUTCDate(0);
+print(d.toISOString());
+
+print(new Date(0).toUTCString(), new Date(0 - 1).toISOString());
+var before = new Date(0 - 86400000);
+print(before.toUTCString(), before.toISOString(), before.getUTCHours());
+var ancient = new Date(0 - 100000000000000);
+print(ancient.toUTCString(), ancient.toISOString(), ancient.toString().slice(0, 15));
+print(new Date(8640000000000000).toISOString());
+print(new Date(8640000000000001).getTime());
+print(new Date(99, 0).getFullYear());
+print(new Date("Tue, 01 Mar 2016 10:00:00 GMT").valueOf());
+print(new Date(0) + "");
+
+var now = new Date();
+print(now.toString(), now.getTimezoneOffset(), now.getHours(), now.getUTCHours());
+print(Date());
@@ -0,0 +1,17 @@
+var add = new Function("a", "b", "return a + b");
+print(add(2, 3), add.length, add.name);
+var greet = Function("name", "return 'hello ' + name");
+print(greet("world"));
+var three = new Function("a, b", "c", "return a + b + c");
+print(three(1, 2, 3), three.length);
+var empty = new Function();
+print(empty() == undefined, empty.length);
+print(add.toString());
+print(empty.toString());
+var counter = new Function("this.count = 1");
+var c = new counter();
+print(c.count);
+var total = 10;
+var readGlobal = new Function("return total");
+print(readGlobal());
+print(add.call(null, "x", "y"), Function.length);
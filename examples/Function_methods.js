@@ -0,0 +1,40 @@
+var point = new Object();
+point.x = 3;
+point.y = 4;
+function sum(a, b) {
+    return this.x + this.y + a + b;
+}
+print(sum.call(point, 1, 2), sum.apply(point, [10, 20]), sum.length, sum.name);
+
+var args = new Object();
+args.length = 2;
+args[0] = "a";
+args[1] = "b";
+function join(a, b, c) {
+    return a + b + c;
+}
+print(join.apply(null, args), join.call(null, 1, 2, 3));
+
+var add = sum.bind(point, 100);
+print(add(200), add.length, add.name);
+var bound = join.bind(null, "x", "y", "z", "w");
+print(bound(), bound.length, bound.name);
+
+var self = function () { return this; };
+print(self.call(undefined) == globalThis, self.call(point) == point);
+
+function Pair(a, b) {
+    this.a = a;
+    this.b = b;
+}
+var P = Pair.bind(null, 1);
+var pair = new P(2);
+print(pair.a, pair.b);
+
+print(sum.toString());
+print(join + "");
+print(self.toString(), add.toString());
+var max = Math.max;
+print(parseInt.toString(), max.toString());
+print(Function.prototype.call.length, Function.prototype.apply.length, Function.prototype.bind.length);
+print(String.prototype.slice.length, Math.max.length, Object.length, Array.length);
@@ -0,0 +1,14 @@
+print(Math.PI, Math.E, Math.SQRT2);
+print(Math.abs(0 - 4), Math.floor(2.7), Math.ceil(2.1));
+print(Math.round(2.5), Math.round(0 - 0.5), Math.round(0 - 2.5));
+print(Math.max(), Math.min());
+print(Math.max(1, 3, 2), Math.min(1, 3, 2));
+print(Math.max(1, Math.sqrt(0 - 1), 2));
+print(Math.pow(2, 10), Math.pow(1, Math.max()));
+print(Math.atan2(1, 1), Math.log(Math.E), Math.exp(0));
+
+Math.PI = 3;
+print(Math.PI);
+
+var r = Math.random();
+print(r);
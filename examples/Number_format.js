@@ -0,0 +1,20 @@
+print(1e21, 1e-7, 123.456, 0.000001, 100, 0.3333333333333333);
+print((255).toString(16), (255).toString(2), (0.5).toString(2), (3.75).toString(8));
+var pi = Math.PI;
+print((0 - 255).toString(36), (0.1).toString(3), pi.toString(16));
+print((1.005).toFixed(2), (0.5).toFixed(0), (1.5).toFixed(0), (2.5).toFixed(0));
+print((1.45).toFixed(1), (1e21).toFixed(2), (0 - 1.5).toFixed(0), (0 - 0.0001).toFixed(2));
+print((123.456).toFixed(10), (0).toFixed(2), (0.000001).toFixed(7));
+print((123456).toExponential(2), (0).toExponential(), (0).toExponential(2), (1.5e-7).toExponential());
+print((12345).toExponential(), (0.00015).toExponential(1), (0 - 5).toExponential(3));
+print((123.456).toPrecision(4), (0.000123).toPrecision(2), (123456).toPrecision(2), (1e21).toPrecision(3));
+print((0).toPrecision(3), (1.25).toPrecision(2), (99.99).toPrecision(3), (0.00000123).toPrecision(2));
+print(Number.MAX_VALUE, Number.MIN_VALUE, Number.EPSILON, Number.MAX_SAFE_INTEGER);
+print(Number.NaN, Number.POSITIVE_INFINITY, Number.NEGATIVE_INFINITY);
+print(Number.isInteger(5), Number.isInteger(5.5), Number.isInteger("5"), Number.isFinite(Number.POSITIVE_INFINITY));
+print(Number.isNaN(Number.NaN), Number.isNaN("x"), Number.isSafeInteger(9007199254740992));
+Number.MAX_VALUE = 1;
+print(Number.MAX_VALUE);
+print((0.000001234).toString(7), (1e300).toString(36), (123.456).toString(36), (2e-7).toString(2));
+print((5e-324).toFixed(2), (1.7976931348623157e308).toPrecision(1), (0.1 + 0.2).toFixed(20));
+print((1234.5678).toFixed(100).length, (1e-10).toPrecision(1), (25).toExponential(0));
@@ -0,0 +1,33 @@
+var toString = Object.prototype.toString;
+print(toString.call(new Object()), toString.call([1, 2]), toString.call(print));
+print(toString.call(1), toString.call("s"), toString.call(true), toString.call(new Date(0)));
+print(toString.call(new Error("e")), toString.call(undefined), toString.call(null));
+function args() {
+    return toString.call(arguments);
+}
+print(args(), toString.call(Object.create(null)));
+
+var o = new Object();
+o.own = 1;
+var child = Object.create(o);
+child.mine = 2;
+print(child.hasOwnProperty("mine"), child.hasOwnProperty("own"), o.hasOwnProperty("own"));
+print(o.hasOwnProperty("hasOwnProperty"), Object.prototype.hasOwnProperty("hasOwnProperty"));
+print("abc".hasOwnProperty("length"), [1].hasOwnProperty(0), [1].hasOwnProperty("1"));
+
+print(o.isPrototypeOf(child), Object.prototype.isPrototypeOf(child), child.isPrototypeOf(o));
+print(Object.prototype.isPrototypeOf(1), Function.prototype.isPrototypeOf(print));
+print(Object.prototype.isPrototypeOf(Object.create(null)));
+
+print(o.propertyIsEnumerable("own"), child.propertyIsEnumerable("own"));
+print([1].propertyIsEnumerable("length"), [1].propertyIsEnumerable(0));
+print(Object.prototype.propertyIsEnumerable("toString"), Object.keys(Object.prototype).length);
+
+function Point(x) {
+    this.x = x;
+}
+Point.prototype.toString = function () {
+    return "Point(" + this.x + ")";
+};
+print(new Point(1).toLocaleString(), o.toLocaleString(), [1, 2].toLocaleString());
+print(Object.prototype.hasOwnProperty.length, Object.prototype.toLocaleString.name);
@@ -0,0 +1,103 @@
+function show(array) {
+    return "[" + array + "]";
+}
+
+function valueDescriptor(value) {
+    var desc = new Object();
+    desc.value = value;
+    return desc;
+}
+
+var o = new Object();
+o.a = 1;
+o.b = "two";
+o.c = true;
+print(show(Object.keys(o)), show(Object.values(o)));
+print(Object.entries(o).join(" | "));
+print(show(Object.keys([5, 6, 7])), show(Object.getOwnPropertyNames([5, 6])));
+
+var proto = new Object();
+proto.greet = function () {
+    return "hello " + this.name;
+};
+var child = Object.create(proto);
+child.name = "child";
+print(child.greet(), Object.getPrototypeOf(child) == proto, show(Object.keys(child)));
+print(Object.getPrototypeOf(Object.create(null)) == null, Object.getPrototypeOf(1) == Number.prototype);
+
+var other = new Object();
+other.name = "other";
+print(Object.setPrototypeOf(other, proto) == other, other.greet());
+
+var d = new Object();
+Object.defineProperty(d, "hidden", valueDescriptor(42));
+var shown = valueDescriptor("yes");
+shown.enumerable = true;
+shown.writable = true;
+Object.defineProperty(d, "shown", shown);
+d.hidden = 0;
+d.shown = "still yes";
+print(d.hidden, d.shown, show(Object.keys(d)), show(Object.getOwnPropertyNames(d)));
+
+var desc = Object.getOwnPropertyDescriptor(d, "hidden");
+print(desc.value, desc.writable, desc.enumerable, desc.configurable);
+desc = Object.getOwnPropertyDescriptor(o, "a");
+print(desc.value, desc.writable, desc.enumerable, desc.configurable);
+print(Object.getOwnPropertyDescriptor(o, "missing") == undefined);
+
+var props = new Object();
+props.x = valueDescriptor(1);
+props.x.enumerable = true;
+props.y = valueDescriptor(2);
+var e = Object.create(proto, props);
+print(e.x, e.y, show(Object.keys(e)), e.greet == proto.greet);
+Object.defineProperties(e, props);
+
+var arr = [1, 2];
+var element = valueDescriptor(6);
+element.enumerable = true;
+Object.defineProperty(arr, "5", element);
+print(arr.length, show(arr));
+
+var f = new Object();
+f.p = 1;
+print(Object.freeze(f) == f);
+f.p = 2;
+f.q = 3;
+print(f.p, f.q == undefined, Object.isFrozen(f), Object.isSealed(f), Object.isExtensible(f));
+
+var s = new Object();
+s.p = 1;
+Object.seal(s);
+s.p = 2;
+s.q = 3;
+print(s.p, s.q == undefined, Object.isFrozen(s), Object.isSealed(s), Object.isExtensible(s));
+
+var n = new Object();
+Object.preventExtensions(n);
+n.q = 3;
+print(n.q == undefined, Object.isExtensible(n), Object.isFrozen(n), Object.isFrozen(1), Object.isExtensible(1));
+
+var inherits = Object.create(f);
+inherits.p = 5;
+print(inherits.p, show(Object.keys(inherits)));
+
+var target = new Object();
+target.a = 0;
+var src = new Object();
+src.a = 1;
+src.b = 2;
+var result = Object.assign(target, src, null, undefined);
+print(result == target, target.a, target.b, show(Object.keys(target)));
+print(Object.defineProperty(target, "c", valueDescriptor(3)) == target);
+print(show(Object.keys(Object.assign(new Object(), f))));
+print(Object.keys.length, Object.create.length, Object.defineProperty.length, Object.assign.name);
+
+var ordered = new Object();
+ordered.zeta = 1;
+ordered[2] = "two";
+ordered.alpha = 2;
+ordered[0] = "zero";
+ordered.mid = 3;
+print(show(Object.keys(ordered)), show(Object.values(ordered)));
+print(show(Object.keys(Object.assign(new Object(), ordered))));
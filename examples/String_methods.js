@@ -0,0 +1,18 @@
+var s = "Hello, world";
+var w = new String("wrapped");
+
+print(s.length, w.length, "".length);
+print(s.charAt(4), s.charAt(100) + "|", s.charCodeAt(0), s.charCodeAt(100));
+print(s.indexOf("o"), s.indexOf("o", 5), s.indexOf("xyz"), s.indexOf(""));
+print(s.lastIndexOf("o"), s.lastIndexOf("o", 7), s.lastIndexOf("H", 0 - 5));
+print(s.split(", ").length, s.split(", ").join("|"), s.split("").length, s.split().join("|"));
+print(s.split("l", 2).join("|"), "".split(",").length, "".split("").length);
+print(s.substring(7), s.substring(5, 0), s.substr(0 - 5, 3), s.slice(0 - 5, 0 - 1));
+print(s.concat("!", 1, true), "  \t trimmed \n ".trim() + "|");
+print(s.replace("world", "there"), s.replace("o", "[$&]"), s.replace("o", "$`$$"));
+print(s.replace("world", function (m, pos, str) { return m.toUpperCase() + pos; }));
+print(s.startsWith("Hello"), s.startsWith("world", 7), s.endsWith("Hello", 5), s.includes(", "));
+print("ab".repeat(3), "5".padStart(3, "0"), "5".padEnd(4, "xy"), "abc".padStart(2));
+print(String.fromCharCode(72, 105, 65601), w.toUpperCase(), w.charAt(0));
+print([1, 2, 3].join(), [1, 2, 3].join(" - "), new Array(3).length, Array(1, 2).length);
+print(String.prototype.toUpperCase.call(true), String.prototype.toString.call("x"), "".repeat(1e12).length);
@@ -0,0 +1,105 @@
+var temperature = {
+    celsius: 20,
+    get offset() {
+        return this.celsius + 32;
+    },
+    set offset(f) {
+        this.celsius = f - 32;
+    }
+};
+print(temperature.offset);
+temperature.offset = 132;
+print(temperature.celsius, temperature.offset);
+
+var counter = {
+    count: 0,
+    get next() {
+        this.count = this.count + 1;
+        return this.count;
+    }
+};
+print(counter.next, counter.next, counter.count);
+
+var readOnly = {
+    get value() {
+        return "fixed";
+    }
+};
+readOnly.value = "changed";
+print(readOnly.value);
+
+var writeOnly = {
+    set value(v) {
+        this.stored = v;
+    }
+};
+writeOnly.value = "stored";
+print(writeOnly.value == undefined, writeOnly.stored);
+
+var child = Object.create(temperature);
+child.offset = 32;
+print(child.celsius, temperature.celsius, child.hasOwnProperty("celsius"));
+
+var point = { x: 1, y: 2 };
+Object.defineProperty(point, "sum", {
+    get: function () {
+        return this.x + this.y;
+    },
+    enumerable: false
+});
+print(point.sum, Object.keys(point).join());
+point.x = 10;
+print(point.sum);
+
+var desc = Object.getOwnPropertyDescriptor(temperature, "offset");
+print(desc.get == undefined, desc.set == undefined, desc.enumerable, desc.configurable, desc.value == undefined);
+desc = Object.getOwnPropertyDescriptor(point, "sum");
+print(desc.set == undefined, desc.enumerable, desc.configurable, desc.hasOwnProperty("writable"));
+
+Object.defineProperty(point, "x", { get: function () { return 100; } });
+print(point.x, point.sum, Object.getOwnPropertyDescriptor(point, "x").enumerable);
+Object.defineProperty(point, "x", { value: 5, writable: true });
+print(point.x, point.sum);
+
+var frozen = Object.freeze({ get answer() { return 42; } });
+print(frozen.answer, Object.isFrozen(frozen));
+
+var values = { a: 1, get b() { return this.a + 1; } };
+print(Object.values(values).join(), Object.entries(values).join(" "));
+var copy = Object.assign({}, values);
+values.a = 10;
+print(copy.b, Object.getOwnPropertyDescriptor(copy, "b").value);
+
+var target = {
+    set name(v) {
+        this.seen = "set " + v;
+    }
+};
+Object.assign(target, { name: "x" });
+print(target.seen);
+
+var shorthand = 3;
+var literal = { shorthand, "quoted key": 1, 7: "seven", ["comp" + "uted"]: true, method() { return this.shorthand; } };
+print(literal.shorthand, literal["quoted key"], literal[7], literal.computed, literal.method());
+
+function Temperature() {}
+Object.defineProperty(Temperature.prototype, "kelvin", {
+    get: function () {
+        return this.c + 273;
+    },
+    set: function (k) {
+        this.c = k - 273;
+    }
+});
+var t = new Temperature();
+t.kelvin = 300;
+print(t.c, t.kelvin);
+
+var lazyProto = {
+    get value() {
+        Object.defineProperty(this, "value", { value: "computed", enumerable: true });
+        return this.value;
+    }
+};
+var lazy = Object.create(lazyProto);
+print(lazy.value, lazy.hasOwnProperty("value"), Object.keys(lazy).join());
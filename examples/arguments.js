@@ -0,0 +1,57 @@
+function count() {
+    return arguments.length;
+}
+print(count(), count(1), count(1, 2, 3));
+
+function extras(a) {
+    return a + arguments[1] + arguments[2];
+}
+print(extras(1, 2, 3), extras.length);
+
+function alias(a, b) {
+    arguments[0] = "changed";
+    b = "also changed";
+    return a + " " + arguments[1];
+}
+print(alias("x", "y"));
+
+function unmapped(a, b) {
+    b = 5;
+    return arguments.length + " " + arguments[1];
+}
+print(unmapped(1));
+
+function self() {
+    return arguments.callee == self;
+}
+print(self());
+
+function sum() {
+    var total = 0;
+    if (arguments.length == 0) {
+        return total;
+    }
+    return arguments[0] + sum.apply(null, []);
+}
+print(sum(), sum(4));
+
+function inner() {
+    function nested() {
+        return arguments.length;
+    }
+    return nested(1, 2) + arguments.length;
+}
+print(inner(1, 2, 3, 4));
+
+function shadow(arguments) {
+    return arguments;
+}
+print(shadow("param"));
+
+function reassign(a) {
+    a = a + 1;
+    return a;
+}
+var g = 1;
+g = g + 1;
+print(reassign(1), g);
@@ -0,0 +1,34 @@
+var double = x => x + x;
+var add = (a, b) => a + b;
+var block = (a) => { var r = a + 1; return r; };
+var none = () => "none";
+print(double(4), add(2, 3), block(1), none());
+var counter = {
+    count: 0,
+    start: function () {
+        var tick = () => { this.count = this.count + 1; return this.count; };
+        tick();
+        return tick();
+    }
+};
+print(counter.start(), counter.count);
+function outer() {
+    var first = () => arguments[0];
+    var nested = () => () => arguments[1] + this.tag;
+    return first() + " " + nested()();
+}
+print(outer.call({ tag: "!" }, "a", "b"));
+var self = this;
+var globalThisArrow = () => this;
+print(globalThisArrow() == self, globalThisArrow.call({}) == self);
+var bound = counter.start.bind({ count: 10 });
+print(bound());
+var makeAdder = n => m => n + m;
+print(makeAdder(3)(4));
+print(double.hasOwnProperty("prototype"), double.length, add.length);
+print(add.toString());
+print(none.toString());
+function counterFrom(n) { return () => { n = n + 1; return n; }; }
+var next = counterFrom(5);
+next();
+print(next());
@@ -0,0 +1,70 @@
+class Point {
+    constructor(x, y) {
+        this.x = x;
+        this.y = y;
+    }
+    add(other) { return new Point(this.x + other.x, this.y + other.y); }
+    toString() { return `(${this.x}, ${this.y})`; }
+    get sum() { return this.x + this.y; }
+    set both(v) { this.x = v; this.y = v; }
+    static origin() { return new Point(0, 0); }
+}
+var p = new Point(1, 2).add(new Point(3, 4));
+print(`${p}`, p.sum, p instanceof Point, Point.origin().sum);
+p.both = 7;
+print(p.x, p.y, Object.keys(p).join(","), Object.keys(Point.prototype).length);
+print(Point.name, Point.length, Point.prototype.constructor == Point);
+print(Point.prototype.add.name, Point.origin.name, Object.getOwnPropertyDescriptor(Point.prototype, "sum").get.name);
+
+class Point3 extends Point {
+    constructor(x, y, z) {
+        super(x, y);
+        this.z = z;
+    }
+    get sum() { return super.sum + this.z; }
+    toString() { return "3d" + super.toString() + this.z; }
+    static origin() { return new Point3(0, 0, 0); }
+}
+var q = new Point3(1, 2, 3);
+print(`${q}`, q.sum, q instanceof Point3, q instanceof Point, q.add(p).sum);
+print(Object.getPrototypeOf(Point3) == Point, Point3.origin().z, Point3.length);
+
+class Named extends Point {
+    name() { return "named" + this.x; }
+}
+var n = new Named(5, 6);
+print(n.name(), n.sum, Named.origin() instanceof Named);
+
+class ValidationError extends Error {
+    constructor(field) {
+        super(`invalid ${field}`);
+        this.field = field;
+    }
+    get name() { return "ValidationError"; }
+}
+var e = new ValidationError("age");
+print(e instanceof ValidationError, e instanceof Error, e.message, e.field, e.name, `${e}`);
+
+var Anonymous = class { hello() { return "hi"; } };
+var Inner = class Self { me() { return Self; } };
+print(new Anonymous().hello(), new Inner().me() == Inner, Inner.name);
+
+class Counter {
+    constructor() { this.count = 0; }
+    increment() {
+        var add = () => { this.count = this.count + 1; return this; };
+        return add();
+    }
+}
+print(new Counter().increment().increment().count);
+
+class Base {
+    greet(who) { return "hello " + who; }
+}
+class Loud extends Base {
+    greet(...who) { return super.greet(...who) + "!"; }
+}
+print(new Loud().greet("you"), String(Point).length, `${class {}}`);
+
+class Empty extends null {}
+print(Object.getPrototypeOf(Empty.prototype) == null, Object.getPrototypeOf(Empty) == Function.prototype);
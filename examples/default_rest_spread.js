@@ -0,0 +1,29 @@
+function f(a, b = a + 1, c = b + 1) { return a + " " + b + " " + c; }
+print(f(1), f(1, 5), f(1, undefined, 0), f(1, null));
+print(f.length);
+function g(a, ...rest) { return rest.length + ":" + rest.join(","); }
+print(g(1), g(1, 2, 3), g.length);
+function h(a, b = 2, c) {} print(h.length);
+var arrow = (x, y = x + x) => x + y;
+print(arrow(3), arrow(3, 1), arrow.length);
+var restArrow = (...xs) => xs.join("-");
+print(restArrow(1, 2, 3), restArrow.length);
+function unmapped(a = 0) { a = 5; return arguments[0] + " " + arguments.length; }
+print(unmapped(1), unmapped());
+function mapped(a) { a = 5; return arguments[0]; }
+print(mapped(1));
+function sum(a, b, c) { return a + b + c; }
+var xs = [1, 2, 3];
+print(sum(...xs), sum(0, ...[4, 5]), sum(..."abc"));
+var o = { base: 10, add: function (a, b) { return this.base + a + b; } };
+print(o.add(...[1, 2]));
+var ys = [0, ...xs, 4, ...[5, 6]];
+print(ys.length, ys.join(","));
+function Point(x, y) { this.x = x; this.y = y; }
+var p = new Point(...[7, 8]);
+print(p.x, p.y);
+function spreadArgs() { return sum(...arguments); }
+print(spreadArgs(1, 2, 3));
+print([..."a😀b"].length);
+function withArgs(a, b = arguments.length) { return b; }
+print(withArgs(1), withArgs(1, 2, 3));
@@ -0,0 +1,64 @@
+var [a, b] = [1, 2];
+print(a, b);
+var { x, y: why, z = 3 } = { x: 4, y: 5 };
+print(x, why, z);
+let [p, [q, r = "r"], ...others] = [1, [2], 3, 4, 5];
+print(p, q, r, others.length, others.join(","));
+const { k, nested: { deep }, ...leftover } = { k: 1, nested: { deep: "d" }, m: 2, n: 3 };
+print(k, deep, leftover.m, leftover.n, leftover.hasOwnProperty("k"));
+var key = "dyn";
+var { [key]: dynamic, ["other" + "Key"]: other = "default" } = { dyn: "D" };
+print(dynamic, other);
+var [c1, , c3] = "abc";
+print(c1, c3);
+var swap1 = 1, swap2 = 2;
+[swap1, swap2] = [swap2, swap1];
+print(swap1, swap2);
+var target = {};
+({ a: target.first, b: target["second"] } = { a: "A", b: "B" });
+print(target.first, target.second);
+var result = ([a, b] = [7, 8]);
+print(a, b, result.length);
+function f({ name, tags: [first, ...rest] = [] } = {}, [n = 10] = []) {
+    return name + " " + first + " " + rest.length + " " + n;
+}
+print(f({ name: "x", tags: ["t1", "t2", "t3"] }, [1]), f(), f.length);
+var g = ({ a }, [b]) => a + b;
+print(g({ a: 1 }, [2]), g.length);
+function withArgs({ a }) { return arguments.length + " " + a; }
+print(withArgs({ a: 1 }, 2));
+var sum = 0;
+for (var [i, v] of [[1, 10], [2, 20]]) { sum = sum + i + v; }
+print(sum);
+for (const { id } of [{ id: "a" }, { id: "b" }]) { print(id); }
+var keys = "";
+for (var name in { alpha: 1, beta: 2 }) { keys = keys + name; }
+print(keys);
+var out = [];
+for ([out[0], out[1]] of [[5, 6]]) {}
+print(out.join(","));
+var fns = [];
+var count = 0;
+for (let e of [1, 2, 3]) { fns[count] = () => e; count = count + 1; }
+print(fns[0](), fns[1](), fns[2]());
+for (let ch of "hi") { print(ch); }
+function Proto() {} Proto.prototype.inherited = 1;
+var own = new Proto(); own.mine = 2;
+var seen = "";
+for (var prop in own) { seen = seen + prop + ";"; }
+print(seen);
+for (var item of [1, 2, 3]) { if (item == 2) { break; } print(item); }
+print([1, 2].length);
+var kept = "";
+for (var key in { a: 1, b: 2, c: 3 }) { if (key == "b") { continue; } kept = kept + key; }
+for (let n of [1, 2, 3]) { let twice = n + n; if (n == 2) { continue; } kept = kept + twice; }
+print(kept);
+var pairs = "";
+rows: for (let row of [1, 2, 3]) {
+    for (let col in { x: 1, y: 2 }) {
+        if (col == "y") { continue rows; }
+        if (row == 3) { break rows; }
+        pairs = pairs + row + col + " ";
+    }
+}
+print(pairs);
@@ -0,0 +1,11 @@
+print(parseInt("  42px"), parseInt("-0x1F"), parseInt("ff", 16), parseInt("0x10", 16));
+print(parseInt("101", 2), parseInt("z", 36), parseInt("12", 1), parseInt(""), parseInt("08"));
+print(parseInt("123456789012345678901234567890"), parseInt("  +7e3"));
+print(parseFloat("3.14abc"), parseFloat(" -.5e2x"), parseFloat("Infinityx"), parseFloat("e5"), parseFloat("1.e3"));
+print(isNaN("abc"), isNaN("  12  "), isNaN(undefined), isNaN(null), isFinite("1e400"), isFinite("0x1A"));
+print(NaN, Infinity, undefined == null, Number("0x1A") + 1, Number("") + 1, Number(" 1e3 ") + 1, Number("1 2"));
+print(escape("a b+c/ä€"), unescape("%u20AC%E4%2"), unescape(escape("xĀy")));
+print(encodeURI("http://x.y/a b?q=ä&r=#f"), encodeURIComponent("a b&c=d/é😀"));
+print(decodeURI("%41%2F%23%E2%82%AC"), decodeURIComponent("%41%2F%23%E2%82%AC%F0%9F%98%80"));
+print(new TypeError("bad").toString(), RangeError("r").message, new URIError().toString(), Error("x").name);
+print(new Error("boom") + "", new SyntaxError("s").name + ":" + String(new EvalError("e")));
@@ -0,0 +1,13 @@
+var answer = 42;
+print(this.answer, globalThis.answer, globalThis == this);
+this.greeting = "hello";
+print(greeting, globalThis.print == print, this.Math == Math);
+globalThis.parseInt = parseFloat;
+print(parseInt("1.5"));
+NaN = 1;
+undefined = 2;
+print(NaN, undefined == null);
+function f() {
+    return 7;
+}
+print(globalThis.f(), this.undefinedVariable == null);
@@ -0,0 +1,20 @@
+function Animal() {}
+function Dog() {}
+Dog.prototype = Object.create(Animal.prototype);
+var rex = new Dog();
+Object.setPrototypeOf(rex, Dog.prototype);
+print(rex instanceof Dog, rex instanceof Animal, rex instanceof Object, rex instanceof Array);
+print(Dog instanceof Function, Object instanceof Object, Function instanceof Function);
+print([] instanceof Array, [] instanceof Object, 5 instanceof Number, new Number(5) instanceof Number);
+print(new TypeError("boom") instanceof Error, new Error("boom") instanceof TypeError);
+
+var BoundDog = Dog.bind(null);
+print(rex instanceof BoundDog);
+
+var point = { x: 1, y: undefined };
+print("x" in point, "y" in point, "z" in point, "toString" in point);
+var child = Object.create(point);
+print("x" in child, child.hasOwnProperty("x"));
+var list = [10, 20];
+print(0 in list, 1 in list, 2 in list, "length" in list, "join" in list);
+print("length" in "abc".split(""), "prototype" in Dog);
@@ -0,0 +1,81 @@
+let a = 1;
+const b = "const";
+{
+    let a = 2;
+    print(a, b);
+    {
+        const a = 3;
+        print(a);
+    }
+    print(a);
+}
+print(a);
+let fns = [];
+for (let i = 0; i - 3; i = i + 1) {
+    fns[i] = function () { return i; };
+}
+print(fns[0](), fns[1](), fns[2]());
+var vfns = [];
+for (var j = 0; j - 3; j = j + 1) {
+    vfns[j] = function () { return j; };
+}
+print(vfns[0](), vfns[2]());
+function counter() {
+    let count = 0;
+    return function () {
+        count = count + 1;
+        return count;
+    };
+}
+let c1 = counter(), c2 = counter();
+c1(); c1();
+print(c1(), c2());
+function shadowParam(x) {
+    {
+        let x = "block";
+        print(x);
+    }
+    return x;
+}
+print(shadowParam("param"));
+let undef;
+print(undef == undefined);
+switch (1) {
+    case 1:
+        let inCase = "case scoped";
+        print(inCase);
+        break;
+}
+for (let k = 0; k - 5; k = k + 1) {
+    if (k == 2) { break; }
+    print("k", k);
+}
+with ({ w: "with" }) {
+    let w2 = w + "!";
+    print(w2);
+}
+var skipped = "";
+for (let k = 0; k - 5; k = k + 1) {
+    if (k == 2) continue;
+    skipped = skipped + k;
+}
+print(skipped);
+var pairs = "";
+outer: for (var i = 0; i - 3; i = i + 1) {
+    for (let j = 0; j - 3; j = j + 1) {
+        if (j == 1) continue outer;
+        if (i == 2) break outer;
+        pairs = pairs + i + j + " ";
+    }
+}
+print(pairs);
+labelled: {
+    print("before break");
+    if (pairs) break labelled;
+    print("not reached");
+}
+var local = "global";
+function shadow() { var local = "function"; return local; }
+function fact(n) { var r = 1; if (n - 1) { r = fact(n - 1) + n + n; } return r; }
+function param(a) { var a; return a; }
+print(shadow(), local, fact(3), param(5));
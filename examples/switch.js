@@ -0,0 +1,40 @@
+function name(n) {
+    switch (n) {
+        case 1: return "one";
+        case 2:
+        case 3: return "two or three";
+        default: return "many";
+        case 0: return "zero";
+    }
+}
+print(name(0), name(1), name(2), name(3), name(7), name("1"));
+function kind(x) {
+    var out = "";
+    switch (x) {
+        case "a": out = out + "a";
+        case "b": out = out + "b"; break;
+        case "c": out = out + "c";
+    }
+    return out;
+}
+print(kind("a"), kind("b"), kind("c"), kind("d") == "");
+var log = "";
+function t(v) { log = log + v; return v; }
+function probe(x) {
+    log = "";
+    switch (x) {
+        case t(1): log = log + "!1";
+        default: log = log + "!d";
+        case t(2): log = log + "!2"; break;
+        case t(3): log = log + "!3";
+    }
+    return log;
+}
+print(probe(1), probe(2), probe(3), probe(4));
+switch (undefined) { case null: print("null"); break; case undefined: print("undefined matched"); }
+switch (5) { default: print("only default"); }
+switch (5) { }
+
+switch (0 - 0.5 + 0.5) { case 0: print("zero by table"); break; case 1: print("one"); }
+switch ("2") { case 2: print("number"); break; case "2": print("string"); }
+print("end");
@@ -0,0 +1,38 @@
+var name = "world", n = 2;
+print(`hello ${name}!`);
+print(`${n} + ${n} = ${n + n}`);
+print(`multi
+line`);
+print(`escapes: \t|A|\x42|\`|\${}`);
+print(`${[1, 2]}|${{}}|${null}|${undefined}|${true}`);
+var valued = { valueOf: function () { return 1; }, toString: function () { return "str"; } };
+print(`${valued}`, "" + valued);
+print(`nested ${`inner ${name}`}`);
+print(``.length);
+function tag(strings, ...subs) {
+    return strings.length + ":" + strings.join("|") + ":" + strings.raw.join("|") + ":" + subs.join(",");
+}
+print(tag`a${1}b\n${2}c`);
+print(tag``);
+function site(strings) { return strings; }
+function twice() { return site`x${1}y`; }
+print(twice() == twice(), site`x${1}y` == site`x${1}y`);
+var s = twice();
+print(Object.isFrozen(s), Object.isFrozen(s.raw), s.hasOwnProperty("raw"), Object.keys(s).length);
+var logger = {
+    prefix: "[log]",
+    log: function (strings, ...values) {
+        var out = this.prefix;
+        for (var i = 0; i - strings.length; i = i + 1) {
+            out = out + strings[i];
+            if (i - values.length) { out = out + "<" + values[i] + ">"; }
+        }
+        return out;
+    }
+};
+print(logger.log`user ${name} logged in ${n} times`);
+print(String(`a${1}`));
+function f() { return `}${"{"}`; }
+print(f.toString());
+var g = () => `${1}}`;
+print(g.toString(), f(), g());
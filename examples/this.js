@@ -0,0 +1,59 @@
+function whoami() {
+    return this.name;
+}
+var name = "global";
+var a = new Object();
+a.name = "a";
+a.whoami = whoami;
+a.b = new Object();
+a.b.name = "b";
+a.b.whoami = whoami;
+print(whoami(), a.whoami(), a.b.whoami(), a["whoami"]());
+
+function id(x) {
+    return x;
+}
+print(id(a.whoami()), a.b.whoami() + a.whoami());
+
+a.self = function () {
+    return this;
+};
+print(a.self() == a, a.b.whoami.call(a), id(a.self()).b.whoami());
+
+var key = "whoami";
+print(a.b[key]());
+
+var detached = a.whoami;
+print(detached());
+
+function Point(x) {
+    this.x = x;
+    this.getX = function () {
+        return this.x;
+    };
+}
+var p = new Point(3);
+print(p.getX(), new Point(4).getX());
+
+String.prototype.twice = function () {
+    return this + this;
+};
+print("ab".twice());
+
+function strict() {
+    "use strict";
+    return this;
+}
+print(strict() == undefined, strict.call(a) == a);
+
+function sloppy() {
+    return this;
+}
+print(sloppy() == globalThis, sloppy.call(null) == globalThis);
+// The callee and its this value are evaluated before the arguments
+var first = { m: function () { return "first"; } };
+var second = { m: function () { return "second"; } };
+print(first.m(first = second));
+function outer() { print("outer"); return function (x) { return x; }; }
+function inner() { print("inner"); return "result"; }
+print(outer()(inner()));
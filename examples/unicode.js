@@ -0,0 +1,11 @@
+var s = "héllo 😀";
+print(s, s.length, s.charCodeAt(1), s.charCodeAt(6), s.charCodeAt(7));
+print(s.charAt(6).length, s.slice(6).length, s.indexOf("\uDE00"));
+
+var lone = "\uD800";
+print(lone.length, lone.charCodeAt(0), (lone + "x").length);
+print("\u{1F600}".length, "\x41\101\t|", 'single \'quoted\'');
+print(String.fromCharCode(55357, 56832) == "😀", "a" == "a", "a" == "b");
+print("ÉCOLE".toLowerCase(), "straße".toUpperCase());
+print("line\
+continued");
@@ -0,0 +1,56 @@
+var a, b, c;
+a = 1, b = 2, c = (a, b, 3);
+print(a, b, c);
+var x = (1, 2, "last");
+print(x);
+print((a = 5, a + 1), a);
+function pair(p, q) { return p + "," + q; }
+print(pair((1, 2), 3));
+var point = { x: 10, y: 20 };
+var y = "outer";
+with (point) {
+    print(x, y);
+    x = 11;
+    z = "global";
+}
+print(point.x, x, z, point.hasOwnProperty("z"));
+var outer = { name: "outer", value: 1 };
+var inner = Object.create(outer);
+inner.name = "inner";
+with (outer) {
+    with (inner) {
+        print(name, value);
+        value = 2;
+    }
+    print(name, value);
+}
+print(inner.hasOwnProperty("value"), outer.value);
+function shadow(x, extra) {
+    with ({ x: "property" }) {
+        print(x, extra);
+        extra = "changed";
+    }
+    return x + " " + extra;
+}
+print(shadow("param", "arg"));
+function leave(v) {
+    switch (v) {
+        case 1:
+            with ({ v: "inner" }) {
+                print(v);
+                break;
+            }
+        case 2:
+            print("not reached");
+    }
+    return v;
+}
+print(leave(1));
+with ("abc") { print(length, charAt(1)); }
+var getter = { get g() { return "from getter"; } };
+with (getter) { print(g); }
+var label = "global label";
+function readLabel() { return label; }
+with ({ label: "with label" }) {
+    print(label, readLabel());
+}
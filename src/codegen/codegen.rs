@@ -2,8 +2,10 @@ extern crate ressa;
 use resast::prelude::*;
 use ressa::Parser;
 
+use super::source::FunctionSources;
 use crate::vm::code::*;
 use crate::vm::context::Context;
+use crate::vm::jsstring::JSString;
 use crate::vm::value::Value;
 
 // use std::borrow::Cow;
@@ -14,53 +16,150 @@ struct CodeGen<'a> {
     instrs: Vec<Instruction>,
     consts: Vec<Value>,
     names: Vec<String>,
+    tables: Vec<JumpTable>,
     index_of_name: HashMap<String, usize>,
     index_of_param: HashMap<String, usize>,
     is_func: bool,
     in_load_prop: bool,
     ctx: &'a Context,
-    this_stack_len: usize,
-    in_call_expr: bool,
+    strict: bool,
+    sources: Rc<FunctionSources>,
+    // The enclosing statements break and continue can jump out of, and the
+    // labels in front of the statement being compiled
+    targets: Vec<JumpTarget>,
+    labels: Vec<String>,
+    // Number of enclosing with statements
+    with_depth: usize,
+    // Number of scopes pushed by enclosing with statements and blocks
+    scope_depth: usize,
+    // The let and const names of each block, and the blocks being compiled
+    blocks: Vec<Vec<(String, bool)>>,
+    open_blocks: Vec<usize>,
+    // Whether this is the code of an arrow function, and whether it or an
+    // arrow function in it uses the arguments object
+    arrow: bool,
+    uses_arguments: bool,
+    // Whether the parameters are all plain names, and the index of the rest
+    // parameter
+    simple_params: bool,
+    rest: Option<usize>,
+    // The var and function names of the code, besides its parameters
+    vars: Vec<String>,
 }
 
 impl<'a> CodeGen<'a> {
-    fn new(is_func: bool, ctx: &'a Context) -> Self {
+    fn new(is_func: bool, ctx: &'a Context, sources: Rc<FunctionSources>) -> Self {
         CodeGen {
             instrs: Vec::new(),
             consts: Vec::new(),
             names: Vec::new(),
+            tables: Vec::new(),
             index_of_name: HashMap::new(),
             index_of_param: HashMap::new(),
             is_func,
             in_load_prop: false,
             ctx,
-            this_stack_len: 0,
-            in_call_expr: false,
+            strict: false,
+            sources,
+            targets: Vec::new(),
+            labels: Vec::new(),
+            with_depth: 0,
+            scope_depth: 0,
+            blocks: Vec::new(),
+            open_blocks: Vec::new(),
+            arrow: false,
+            uses_arguments: false,
+            simple_params: true,
+            rest: None,
+            vars: Vec::new(),
         }
     }
+    // Code generator for a function nested in this code
+    fn nested(&self) -> Self {
+        let mut codegen = Self::new(true, self.ctx, self.sources.clone());
+        codegen.strict = self.strict;
+        codegen
+    }
+    fn into_code(self) -> Code {
+        let mut code = Code::new(self.instrs, self.consts, self.names);
+        code.tables = self.tables;
+        code.blocks = self.blocks;
+        code.params = vec![String::new(); self.index_of_param.len()];
+        for (name, idx) in self.index_of_param {
+            if idx < code.params.len() {
+                code.params[idx] = name;
+            }
+        }
+        code.strict = self.strict;
+        code.arrow = self.arrow;
+        code.simple_params = self.simple_params;
+        code.rest = self.rest;
+        code.vars = self.vars;
+        code
+    }
     fn gen(src: String, ctx: &'a Context) -> Code {
         let mut parser = Parser::new(src.as_str()).expect("Failed to create parser");
         let program = parser.parse().expect("Unabl eto parse");
-        let mut codegen = CodeGen::new(false, ctx);
+        let sources = Rc::new(FunctionSources::new(&src));
+        let mut codegen = CodeGen::new(false, ctx, sources);
         match program {
             Program::Script(parts) => codegen.code(parts),
             Program::Mod(_parts) => panic!("Modules not implemented"),
         };
-        Code::new(codegen.instrs, codegen.consts, codegen.names)
+        codegen.into_code()
     }
     fn code(&mut self, parts: Vec<ProgramPart>) {
-        for p in parts {
+        // Only the directive prologue is parsed as directives
+        for p in parts.iter() {
+            match p {
+                ProgramPart::Dir(dir) if dir.dir == "use strict" => self.strict = true,
+                ProgramPart::Dir(_) => (),
+                _ => break,
+            }
+        }
+        // Var names of a function are local to it, while those of a script
+        // are properties of the global object. A var shares the binding of a
+        // parameter of the same name, unless the parameters aren't simple
+        // (ES6 9.2.12)
+        let mut vars = Vec::new();
+        var_names(&parts, &mut vars);
+        for name in vars {
+            let shared = self.simple_params && self.index_of_param.contains_key(&name);
+            if !shared && !self.vars.contains(&name) {
+                self.vars.push(name);
+            }
+        }
+        if self.is_func && !self.vars.is_empty() {
+            self.instrs.push(Instruction::PushVarScope);
+        }
+        // The scope of the top level needs no popping, as the code ends with it
+        let names = lexical_names(&parts);
+        if !names.is_empty() {
+            self.enter_block(names);
+        }
+        // Function declarations are initialized on entry, before the other
+        // statements run (ES5 10.5)
+        let (funcs, parts): (Vec<_>, Vec<_>) = parts
+            .into_iter()
+            .partition(|p| matches!(p, ProgramPart::Decl(Decl::Func(_))));
+        for p in funcs.into_iter().chain(parts) {
             match p {
                 ProgramPart::Stmt(stmt) => self.visit_stmt(stmt),
                 ProgramPart::Decl(decl) => self.visit_decl(decl),
-                _ => panic!("Not impl"),
+                ProgramPart::Dir(dir) => {
+                    self.visit_expr(Expr::Lit(dir.expr));
+                    self.instrs.push(Instruction::Pop);
+                }
             }
         }
     }
     fn visit_stmt(&mut self, stmt: Stmt) {
         match stmt {
             Stmt::Empty => (),
-            Stmt::Expr(expr) => self.visit_expr(expr),
+            Stmt::Expr(expr) => {
+                self.visit_expr(expr);
+                self.instrs.push(Instruction::Pop);
+            }
             Stmt::Return(ret) => {
                 if let Some(expr) = ret {
                     self.visit_expr(expr);
@@ -88,81 +187,341 @@ impl<'a> CodeGen<'a> {
                 self.instrs[index2] = Instruction::Jump(index3 + 1);
             }
             Stmt::Block(block) => {
-                for p in block.0 {
-                    match p {
-                        ProgramPart::Stmt(stmt) => self.visit_stmt(stmt),
-                        ProgramPart::Decl(decl) => self.visit_decl(decl),
-                        _ => panic!("Not impl"),
+                let names = lexical_names(&block.0);
+                if names.is_empty() {
+                    self.visit_parts(block.0);
+                } else {
+                    self.enter_block(names);
+                    self.visit_parts(block.0);
+                    self.leave_block();
+                }
+            }
+            Stmt::Switch(SwitchStmt {
+                discriminant,
+                cases,
+            }) => {
+                self.visit_expr(discriminant);
+                // The case clauses share one block scope
+                let parts: Vec<_> = cases.iter().flat_map(|c| c.consequent.iter()).collect();
+                let names = lexical_names(parts);
+                let scoped = !names.is_empty();
+                if scoped {
+                    self.enter_block(names);
+                }
+                self.push_target(TargetKind::Switch, 0);
+                match Self::jump_table_keys(&cases) {
+                    Some(keys) => self.visit_switch_table(keys, cases),
+                    None => self.visit_switch_cases(cases),
+                }
+                self.patch_breaks();
+                if scoped {
+                    self.leave_block();
+                }
+            }
+            Stmt::For(ForStmt {
+                init,
+                test,
+                update,
+                body,
+            }) => self.visit_for(init, test, update, *body),
+            Stmt::ForIn(ForInStmt { left, right, body }) => {
+                self.visit_for_in_of(left, right, *body, false)
+            }
+            Stmt::ForOf(ForOfStmt {
+                left, right, body, ..
+            }) => self.visit_for_in_of(left, right, *body, true),
+            Stmt::Break(label) => self.visit_jump(label, false),
+            Stmt::Continue(label) => self.visit_jump(label, true),
+            // A label names the loop or switch it is in front of, or else a
+            // statement of its own that only a labelled break leaves
+            Stmt::Labeled(LabeledStmt { label, body }) => {
+                self.labels.push(label.name.to_string());
+                match *body {
+                    body @ Stmt::For(_)
+                    | body @ Stmt::ForIn(_)
+                    | body @ Stmt::ForOf(_)
+                    | body @ Stmt::Switch(_)
+                    | body @ Stmt::Labeled(_) => self.visit_stmt(body),
+                    body => {
+                        self.push_target(TargetKind::Labelled, 0);
+                        self.visit_stmt(body);
+                        self.patch_breaks();
                     }
                 }
             }
+            Stmt::With(WithStmt { object, body }) => {
+                if self.strict {
+                    panic!("Strict mode code may not include a with statement");
+                }
+                self.visit_expr(object);
+                self.instrs.push(Instruction::EnterWith);
+                self.with_depth += 1;
+                self.scope_depth += 1;
+                self.visit_stmt(*body);
+                self.scope_depth -= 1;
+                self.with_depth -= 1;
+                self.instrs.push(Instruction::PopScope);
+            }
             _ => panic!("Unimplemented stmt, {:?}", stmt),
         }
     }
+    fn enter_block(&mut self, names: Vec<(String, bool)>) {
+        self.instrs.push(Instruction::PushScope(self.blocks.len()));
+        self.open_blocks.push(self.blocks.len());
+        self.blocks.push(names);
+        self.scope_depth += 1;
+    }
+    fn leave_block(&mut self) {
+        self.instrs.push(Instruction::PopScope);
+        self.open_blocks.pop();
+        self.scope_depth -= 1;
+    }
+    // Whether an enclosing block of this code declares the name with let or
+    // const
+    fn is_block_bound(&self, name: &str) -> bool {
+        self.open_blocks
+            .iter()
+            .any(|&idx| self.blocks[idx].iter().any(|(n, _)| n == name))
+    }
+    // Starts a statement break can jump out of, which keeps `values` on the
+    // data stack
+    fn push_target(&mut self, kind: TargetKind, values: usize) {
+        self.targets.push(JumpTarget {
+            labels: std::mem::take(&mut self.labels),
+            kind,
+            depth: self.scope_depth,
+            values,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+        });
+    }
+    // A break or continue: leaves the statements and scopes inside its
+    // target, then jumps to be patched with the target's end or next
+    // iteration
+    fn visit_jump(&mut self, label: Option<Ident>, is_continue: bool) {
+        let keyword = if is_continue { "continue" } else { "break" };
+        let found = self.targets.iter().rposition(|target| match label {
+            Some(ref label) => target.labels.iter().any(|name| *name == label.name),
+            None if is_continue => target.kind == TargetKind::Loop,
+            None => target.kind != TargetKind::Labelled,
+        });
+        let idx = match found {
+            Some(idx) => idx,
+            None => panic!("Illegal {} statement", keyword),
+        };
+        if is_continue && self.targets[idx].kind != TargetKind::Loop {
+            panic!("Illegal continue statement: the label does not name a loop");
+        }
+        let values: usize = self.targets[idx + 1..].iter().map(|t| t.values).sum();
+        for _ in 0..values {
+            self.instrs.push(Instruction::Pop);
+        }
+        for _ in self.targets[idx].depth..self.scope_depth {
+            self.instrs.push(Instruction::PopScope);
+        }
+        let index = self.instrs.len();
+        self.instrs.push(Instruction::Jump(0));
+        let target = &mut self.targets[idx];
+        if is_continue {
+            target.continues.push(index);
+        } else {
+            target.breaks.push(index);
+        }
+    }
+    // Points the continues of the innermost loop at the next instruction
+    fn patch_continues(&mut self) {
+        let next = self.instrs.len();
+        let target = self.targets.last_mut().expect("jump targets underflow");
+        for index in target.continues.drain(..) {
+            self.instrs[index] = Instruction::Jump(next);
+        }
+    }
+    // Points the breaks of the innermost breakable statement at its end
+    fn patch_breaks(&mut self) {
+        let end = self.instrs.len();
+        let target = self.targets.pop().expect("jump targets underflow");
+        for index in target.breaks {
+            self.instrs[index] = Instruction::Jump(end);
+        }
+    }
+    // A for statement whose let declarations get a copy of their scope for
+    // each iteration, so closures capture the bindings of one iteration
+    // (ES6 13.6.3.2)
+    fn visit_for(
+        &mut self,
+        init: Option<LoopInit>,
+        test: Option<Expr>,
+        update: Option<Expr>,
+        body: Stmt,
+    ) {
+        let (scoped, per_iteration) = match init {
+            Some(LoopInit::Variable(kind, ref decls)) if kind != VarKind::Var => {
+                self.enter_block(declared_names(kind, decls));
+                (true, kind == VarKind::Let)
+            }
+            _ => (false, false),
+        };
+        match init {
+            Some(LoopInit::Variable(kind, decls)) => self.visit_decl(Decl::Var(kind, decls)),
+            Some(LoopInit::Expr(expr)) => {
+                self.visit_expr(expr);
+                self.instrs.push(Instruction::Pop);
+            }
+            None => (),
+        }
+        if per_iteration {
+            self.instrs.push(Instruction::CopyScope);
+        }
+        let start = self.instrs.len();
+        let exit = test.map(|test| {
+            self.visit_expr(test);
+            self.instrs.push(Instruction::PopJumpIfFalse(0));
+            self.instrs.len() - 1
+        });
+        self.push_target(TargetKind::Loop, 0);
+        self.visit_stmt(body);
+        self.patch_continues();
+        if per_iteration {
+            self.instrs.push(Instruction::CopyScope);
+        }
+        if let Some(update) = update {
+            self.visit_expr(update);
+            self.instrs.push(Instruction::Pop);
+        }
+        self.instrs.push(Instruction::Jump(start));
+        if let Some(exit) = exit {
+            self.instrs[exit] = Instruction::PopJumpIfFalse(self.instrs.len());
+        }
+        self.patch_breaks();
+        if scoped {
+            self.leave_block();
+        }
+    }
+    fn visit_parts(&mut self, parts: Vec<ProgramPart>) {
+        for p in parts {
+            match p {
+                ProgramPart::Stmt(stmt) => self.visit_stmt(stmt),
+                ProgramPart::Decl(decl) => self.visit_decl(decl),
+                _ => panic!("Not impl"),
+            }
+        }
+    }
+    // Case tests run in order, with default only tried after all of them
+    // (ES5 12.11), then the bodies follow one another for fall-through
+    fn visit_switch_cases(&mut self, mut cases: Vec<SwitchCase>) {
+        let mut jumps = Vec::new();
+        for case in cases.iter_mut() {
+            if let Some(test) = case.test.take() {
+                self.visit_expr(test);
+                jumps.push(Some(self.instrs.len()));
+                self.instrs.push(Instruction::JumpIfCase(0));
+            } else {
+                jumps.push(None);
+            }
+        }
+        self.instrs.push(Instruction::Pop);
+        let no_match = self.instrs.len();
+        self.instrs.push(Instruction::Jump(0));
+        let mut default = None;
+        for (case, jump) in cases.into_iter().zip(jumps) {
+            let start = self.instrs.len();
+            match jump {
+                Some(index) => self.instrs[index] = Instruction::JumpIfCase(start),
+                None => default = Some(start),
+            }
+            self.visit_parts(case.consequent);
+        }
+        self.instrs[no_match] = Instruction::Jump(default.unwrap_or(self.instrs.len()));
+    }
+    // The keys of a jump table for these cases, if they are all small integer
+    // or string constants; a repeated key keeps its first case
+    fn jump_table_keys(cases: &[SwitchCase]) -> Option<Vec<Option<Value>>> {
+        let mut keys = Vec::new();
+        for case in cases {
+            keys.push(match case.test {
+                None => None,
+                Some(Expr::Lit(Lit::Number(ref n))) => {
+                    let n = n.parse::<f64>().ok()?;
+                    JumpTable::number_key(n)?;
+                    Some(n.into())
+                }
+                Some(Expr::Lit(Lit::String(StringLit::Double(ref s))))
+                | Some(Expr::Lit(Lit::String(StringLit::Single(ref s)))) => {
+                    Some(string_literal(s).into())
+                }
+                _ => return None,
+            });
+        }
+        Some(keys)
+    }
+    fn visit_switch_table(&mut self, keys: Vec<Option<Value>>, cases: Vec<SwitchCase>) {
+        let mut table = JumpTable::default();
+        self.instrs.push(Instruction::JumpTable(self.tables.len()));
+        let mut default = None;
+        for (case, key) in cases.into_iter().zip(keys) {
+            let start = self.instrs.len();
+            match key {
+                Some(Value::Number(n)) => {
+                    let n = JumpTable::number_key(n).expect("Not a jump table key");
+                    table.numbers.entry(n).or_insert(start);
+                }
+                Some(Value::String(ref s)) => {
+                    table.strings.entry(s.clone()).or_insert(start);
+                }
+                Some(_) => panic!("Not a jump table key"),
+                None => default = Some(start),
+            }
+            self.visit_parts(case.consequent);
+        }
+        table.default = default.unwrap_or(self.instrs.len());
+        self.tables.push(table);
+    }
     fn visit_decl(&mut self, decl: Decl) {
         match decl {
             Decl::Var(kind, decls) => {
-                if kind != VarKind::Var {
-                    panic!("Only 'var' decls supported");
-                }
-                for decl in decls {
-                    match decl {
-                        VarDecl { id, init } => {
-                            if let Some(init) = init {
-                                self.visit_expr(init);
-                            } else {
-                                self.instrs.push(Instruction::LoadUndefined);
-                            }
-                            if let Pat::Ident(ident) = id {
-                                let name = ident.name;
-                                self.instrs.push(Instruction::StoreName(
-                                    match self.index_of_name.get(&name.to_string()) {
-                                        Some(idx) => *idx,
-                                        None => {
-                                            self.names.push(name.to_string());
-                                            self.index_of_name
-                                                .insert(name.to_string(), self.names.len() - 1);
-                                            self.names.len() - 1
-                                        }
-                                    },
-                                ));
-                            } else {
-                                // TODO: Better error messages and handling, not just panic!
-                                panic!("unsupported construct");
-                            }
-                        }
+                for VarDecl { id, init } in decls {
+                    if let Some(init) = init {
+                        self.visit_expr(init);
+                    } else if kind == VarKind::Var {
+                        // The binding made on entry to the code keeps its
+                        // value
+                        continue;
+                    } else {
+                        self.instrs.push(Instruction::LoadUndefined);
                     }
+                    self.visit_pat(id, Some(kind));
                 }
             }
 
-            Decl::Func(Func {
-                id, params, body, ..
-            }) => {
+            Decl::Func(func) => {
+                let source = self.function_source(&func);
+                let Func {
+                    id, params, body, ..
+                } = func;
                 let id = id.expect("function statement requires a name");
-                let mut codegen = Self::new(true, self.ctx);
-                for i in 0..params.len() {
-                    let ref param = params[i];
-                    if let FuncArg::Pat(pat) = param {
-                        if let Pat::Ident(ident) = pat {
-                            let ref name = ident.name;
-                            codegen.index_of_param.insert(name.to_string(), i);
-                        }
-                    }
-                }
+                let mut codegen = self.nested();
+                let length = codegen.visit_params(params);
                 codegen.visit_fnbody(body);
-                let code = Code::new(codegen.instrs, codegen.consts, codegen.names);
+                let code = codegen.into_code();
                 // let obj = value::Value::new_function(Rc::from(code), params.len());
-                let val = self
-                    .ctx
-                    .new_Function(id.name.to_string(), Rc::from(code), params.len());
+                let val =
+                    self.ctx
+                        .new_Function(id.name.to_string(), Rc::from(code), length, source);
                 self.consts.push(val);
                 self.names.push(id.name.to_string());
                 self.instrs
-                    .push(Instruction::LoadConst(self.consts.len() - 1));
+                    .push(Instruction::MakeClosure(self.consts.len() - 1));
                 self.instrs
                     .push(Instruction::StoreName(self.names.len() - 1));
             }
 
+            Decl::Class(class) => {
+                let id = class.id.as_ref().expect("class statement requires a name");
+                let idx = self.name_index(&id.name);
+                self.visit_class(class);
+                self.instrs.push(Instruction::InitBinding(idx));
+            }
+
             _ => panic!("{:?} decl not supported", decl),
         }
     }
@@ -171,10 +530,36 @@ impl<'a> CodeGen<'a> {
     }
     fn visit_expr(&mut self, expr: Expr) {
         match expr {
+            // Substitutions are converted with ToString and concatenated
+            // with the strings around them (ES6 12.2.9.5)
+            Expr::Lit(Lit::Template(TemplateLit {
+                quasis,
+                expressions,
+            })) => {
+                let mut strings = quasis.iter().map(|quasi| template_strings(quasi).0);
+                let first = strings.next().unwrap_or_else(|| "".into());
+                self.consts.push(first);
+                self.instrs
+                    .push(Instruction::LoadConst(self.consts.len() - 1));
+                for (expr, string) in expressions.into_iter().zip(strings) {
+                    self.visit_expr(expr);
+                    self.instrs.push(Instruction::ToString);
+                    self.instrs.push(Instruction::BinAdd);
+                    if !string.unwrap_string().is_empty() {
+                        self.consts.push(string);
+                        self.instrs
+                            .push(Instruction::LoadConst(self.consts.len() - 1));
+                        self.instrs.push(Instruction::BinAdd);
+                    }
+                }
+            }
             Expr::Lit(lit) => {
                 self.instrs.push(Instruction::LoadConst(self.consts.len()));
                 match lit {
-                    Lit::Null => self.instrs.push(Instruction::LoadNull),
+                    Lit::Null => {
+                        self.instrs.pop().expect("Instruction underflow");
+                        self.instrs.push(Instruction::LoadNull);
+                    }
                     Lit::Number(std::borrow::Cow::Borrowed(b)) => {
                         self.consts
                             .push(b.parse::<f64>().unwrap_or_default().into());
@@ -183,11 +568,8 @@ impl<'a> CodeGen<'a> {
                         self.consts
                             .push(b.parse::<f64>().unwrap_or_default().into());
                     }
-                    Lit::String(StringLit::Double(std::borrow::Cow::Owned(b))) => {
-                        self.consts.push(Value::String(b.to_string()));
-                    }
-                    Lit::String(StringLit::Double(std::borrow::Cow::Borrowed(b))) => {
-                        self.consts.push(Value::String(b.to_string()));
+                    Lit::String(StringLit::Double(s)) | Lit::String(StringLit::Single(s)) => {
+                        self.consts.push(Value::String(string_literal(&s)));
                     }
                     Lit::Boolean(b) => {
                         self.instrs.pop().expect("Instruction underflow");
@@ -207,33 +589,46 @@ impl<'a> CodeGen<'a> {
                     BinaryOp::Plus => Instruction::BinAdd,
                     BinaryOp::Minus => Instruction::BinSub,
                     BinaryOp::Equal => Instruction::BinEq,
+                    BinaryOp::InstanceOf => Instruction::BinInstanceOf,
+                    BinaryOp::In => Instruction::BinIn,
                     _ => panic!("operator '{:?}' not supported yet ", operator),
                 });
             }
             Expr::Call(CallExpr { callee, arguments }) => {
-                let len = arguments.len();
-                for arg in arguments {
-                    self.visit_expr(arg);
-                }
-                let this_stack_len = self.this_stack_len;
-                let in_call_expr = self.in_call_expr;
-                self.in_call_expr = true;
-                self.visit_expr(*callee);
-                self.instrs.push(Instruction::Call(len));
-                for _ in 0..(self.this_stack_len - this_stack_len) {
-                    self.instrs.push(Instruction::PopThis);
+                let callee = self.visit_callee(*callee);
+                let len = self.visit_args(arguments);
+                self.instrs.push(Self::call(callee, len));
+            }
+            // The tag is called with the strings, then the substitutions
+            Expr::TaggedTemplate(TaggedTemplateExpr { tag, quasi }) => {
+                let callee = self.visit_callee(*tag);
+                let (cooked, raw) = quasi.quasis.iter().map(template_strings).unzip();
+                // Each site has its own strings array, made once
+                let strings = self.ctx.new_TemplateObject(cooked, raw);
+                self.consts.push(strings);
+                self.instrs
+                    .push(Instruction::LoadConst(self.consts.len() - 1));
+                let len = quasi.expressions.len() + 1;
+                for expr in quasi.expressions {
+                    self.visit_expr(expr);
                 }
-                self.in_call_expr = in_call_expr;
-                self.this_stack_len = this_stack_len;
+                self.instrs.push(Self::call(callee, Some(len)));
             }
             Expr::Ident(Ident { name }) => {
                 if self.in_load_prop {
-                    self.consts.push(Value::String(String::from(name)));
+                    self.consts.push(Value::String(JSString::from(&*name)));
                     self.instrs
                         .push(Instruction::LoadConst(self.consts.len() - 1));
                     return;
                 }
-                if self.is_func {
+                // Within a with statement, the object's properties shadow
+                // parameters, so those are also looked up by name, as are
+                // those shadowed by a var
+                if self.is_func
+                    && self.with_depth == 0
+                    && !self.is_block_bound(&name)
+                    && !self.vars.iter().any(|var| *var == *name)
+                {
                     match self.index_of_param.get(&name.to_string()) {
                         Some(idx) => {
                             self.instrs.push(Instruction::LoadArg(*idx));
@@ -242,32 +637,35 @@ impl<'a> CodeGen<'a> {
                         None => (),
                     }
                 }
-                self.instrs.push(Instruction::LoadName(
-                    match self.index_of_name.get(&name.to_string()) {
-                        Some(idx) => *idx,
-                        None => {
-                            self.names.push(name.to_string());
-                            self.index_of_name
-                                .insert(name.to_string(), self.names.len() - 1);
-                            self.names.len() - 1
-                        }
-                    },
-                ));
+                // Arrow functions find it on the scope chain
+                if self.arrow && name == "arguments" {
+                    self.uses_arguments = true;
+                } else if self.is_func && name == "arguments" {
+                    self.instrs.push(Instruction::LoadArguments);
+                    return;
+                }
+                let idx = self.name_index(&name);
+                self.instrs.push(Instruction::LoadName(idx));
+            }
+            // super.name is looked up from the prototype of the method's home
+            // object (ES6 12.3.5)
+            Expr::Member(MemberExpr {
+                object,
+                property,
+                computed,
+            }) if matches!(*object, Expr::Super) => {
+                self.visit_expr(Expr::This);
+                self.visit_property(*property, computed);
+                self.instrs.push(Instruction::LoadSuperProperty);
             }
             Expr::Member(MemberExpr {
-                object, property, ..
+                object,
+                property,
+                computed,
             }) => {
                 self.visit_expr(*object);
-                if self.in_call_expr {
-                    self.instrs.push(Instruction::PushThis);
-                    self.this_stack_len += 1;
-                    self.in_call_expr = false;
-                }
-                let prev = self.in_load_prop;
-                self.in_load_prop = true;
-                self.visit_expr(*property);
+                self.visit_property(*property, computed);
                 self.instrs.push(Instruction::LoadProperty);
-                self.in_load_prop = prev;
             }
             Expr::Assign(AssignExpr {
                 left,
@@ -277,68 +675,96 @@ impl<'a> CodeGen<'a> {
                 if operator != AssignOp::Equal {
                     panic!("Operator {:?} not supported", operator);
                 }
+                // The assigned value is also the value of the expression
                 self.visit_expr(*right);
+                self.instrs.push(Instruction::Dup);
                 match left {
-                    AssignLeft::Expr(expr) => self.visit_expr(*expr),
-                    AssignLeft::Pat(pat) => self.visit_pat(pat),
-                }
-                let instr = match self.instrs.pop() {
-                    None => panic!("Couldn't compute instr"),
-                    Some(instr) => match instr {
-                        Instruction::LoadProperty => Instruction::StoreProperty,
-                        _ => instr,
-                    },
-                };
-                self.instrs.push(instr);
+                    AssignLeft::Expr(expr) => self.visit_store(*expr),
+                    AssignLeft::Pat(pat) => self.visit_pat(pat, None),
+                }
+            }
+            // The comma operator (ES5 11.14)
+            Expr::Sequence(exprs) => {
+                let len = exprs.len();
+                for (i, expr) in exprs.into_iter().enumerate() {
+                    self.visit_expr(expr);
+                    if i + 1 < len {
+                        self.instrs.push(Instruction::Pop);
+                    }
+                }
+            }
+            // Arrow functions find this on the scope chain, bound under a
+            // name no identifier can have
+            Expr::This if self.arrow => {
+                let idx = self.name_index("this");
+                self.instrs.push(Instruction::LoadName(idx));
             }
             Expr::This => {
                 self.instrs.push(Instruction::LoadThis);
             }
+            Expr::ArrowFunc(arrow) => self.visit_arrow(arrow),
             Expr::New(NewExpr { callee, arguments }) => {
-                let nargs = arguments.len();
-                for expr in arguments {
-                    self.visit_expr(expr);
-                }
                 self.visit_expr(*callee);
-                self.instrs.push(Instruction::New(nargs));
+                let nargs = self.visit_args(arguments);
+                self.instrs.push(match nargs {
+                    Some(nargs) => Instruction::New(nargs),
+                    None => Instruction::NewSpread,
+                });
             }
             Expr::Func(func) => {
                 self.visit_func(func);
             }
-            Expr::Array(els) => {
-                // TODO: Empty array slots
-                let mut len = 0;
-                for el in els {
-                    if let Some(el) = el {
-                        self.visit_expr(el);
-                        len += 1;
+            Expr::Class(class) => self.visit_class(class),
+            Expr::Array(els) => self.visit_elements(els),
+            Expr::Obj(props) => {
+                self.instrs.push(Instruction::MakeObject);
+                for prop in props {
+                    let prop = match prop {
+                        ObjProp::Prop(prop) => prop,
+                        ObjProp::Spread(_) => panic!("Unimplemented object spread"),
+                    };
+                    let Prop {
+                        key,
+                        value,
+                        kind,
+                        computed,
+                        ..
+                    } = prop;
+                    // Shorthand properties `{ a }` only carry the key
+                    let shorthand = match (&key, &value) {
+                        (PropKey::Pat(Pat::Ident(ident)), PropValue::None) => Some(ident.clone()),
+                        _ => None,
+                    };
+                    self.visit_prop_key(key, computed);
+                    match (value, shorthand) {
+                        (PropValue::Expr(expr), _) => self.visit_expr(expr),
+                        (PropValue::None, Some(ident)) => self.visit_expr(Expr::Ident(ident)),
+                        (value, _) => panic!("Unsupported property value {:?}", value),
                     }
+                    self.instrs.push(match kind {
+                        PropKind::Init | PropKind::Method => Instruction::InitProperty,
+                        PropKind::Get => Instruction::InitGetter,
+                        PropKind::Set => Instruction::InitSetter,
+                        PropKind::Ctor => panic!("constructor outside of a class"),
+                    });
                 }
-                self.instrs.push(Instruction::MakeArray(len));
             }
             _ => panic!("Unimplemented {:?}", expr),
         }
     }
 
     fn visit_func(&mut self, func: Func) {
+        let source = self.function_source(&func);
         let (id, params, body) = (func.id, func.params, func.body);
         // let id = match id {
         //     None => String::from ("[anonymous function]"),
         //     Some (id) => id.name.to_string ()
         // };
         // let id = id.expect("function statement requires a name");
-        let mut codegen = Self::new(true, self.ctx);
-        for i in 0..params.len() {
-            let ref param = params[i];
-            if let FuncArg::Pat(pat) = param {
-                if let Pat::Ident(ident) = pat {
-                    let ref name = ident.name;
-                    codegen.index_of_param.insert(name.to_string(), i);
-                }
-            }
-        }
+        let mut codegen = self.nested();
+        let length = codegen.visit_params(params);
         codegen.visit_fnbody(body);
-        let code = Code::new(codegen.instrs, codegen.consts, codegen.names);
+        let code = codegen.into_code();
         // let obj = value::Value::new_function(Rc::from(code), params.len());
         // let val = value::Value::new_functionobject(self.ctx, Rc::from(code), params.len());
         let val = self.ctx.new_Function(
@@ -348,11 +774,12 @@ impl<'a> CodeGen<'a> {
                 "".to_string()
             },
             Rc::from(code),
-            params.len(),
+            length,
+            source,
         );
         self.consts.push(val);
         self.instrs
-            .push(Instruction::LoadConst(self.consts.len() - 1));
+            .push(Instruction::MakeClosure(self.consts.len() - 1));
 
         match id {
             Some(id) => {
@@ -363,11 +790,867 @@ impl<'a> CodeGen<'a> {
             None => (),
         };
     }
-    fn visit_pat(&mut self, _pat: Pat) {
-        panic!("pat");
+    fn visit_arrow(&mut self, arrow: ArrowFuncExpr) {
+        let source = match self.sources.claim_arrow(&arrow) {
+            Some(text) => text.into(),
+            None => "() => { [native code] }".into(),
+        };
+        let mut codegen = self.nested();
+        codegen.arrow = true;
+        let length = codegen.visit_params(arrow.params);
+        match arrow.body {
+            ArrowFuncBody::FuncBody(body) => codegen.visit_fnbody(body),
+            ArrowFuncBody::Expr(expr) => {
+                codegen.visit_expr(*expr);
+                codegen.instrs.push(Instruction::Return);
+            }
+        }
+        let uses_arguments = codegen.uses_arguments;
+        let code = codegen.into_code();
+        let template = self
+            .ctx
+            .new_Function(String::new(), Rc::from(code), length, source);
+        template
+            .unwrap_object()
+            .borrow_mut()
+            .props
+            .shift_remove(&JSString::from("prototype"));
+        self.consts.push(template);
+        let idx = self.consts.len() - 1;
+        if self.arrow {
+            // The scope chain already binds this and arguments
+            self.uses_arguments |= uses_arguments;
+            self.instrs.push(Instruction::MakeClosure(idx));
+        } else {
+            self.instrs
+                .push(Instruction::MakeArrow(idx, uses_arguments));
+        }
+    }
+    // Pushes a class: its constructor, with the methods on its prototype and
+    // the static ones on itself. Class code is strict, and a named class
+    // binds its name in a scope of its own (ES6 14.5.14).
+    fn visit_class(&mut self, class: Class) {
+        let source = match self.sources.claim_class(&class) {
+            Some(text) => text.into(),
+            None => "class { [native code] }".into(),
+        };
+        let Class {
+            id,
+            super_class,
+            body,
+        } = class;
+        let name = id.map(|id| id.name.to_string());
+        if let Some(ref name) = name {
+            self.enter_block(vec![(name.clone(), false)]);
+        }
+        let extends = super_class.is_some();
+        if let Some(super_class) = super_class {
+            self.visit_expr(*super_class);
+        }
+        let (constructor, members): (Vec<_>, Vec<_>) = body
+            .0
+            .into_iter()
+            .partition(|prop| prop.kind == PropKind::Ctor);
+        let mut codegen = self.nested();
+        codegen.strict = true;
+        let length = match constructor.into_iter().next() {
+            Some(Prop {
+                value: PropValue::Expr(Expr::Func(func)),
+                ..
+            }) => {
+                let length = codegen.visit_params(func.params);
+                codegen.visit_fnbody(func.body);
+                length
+            }
+            Some(prop) => panic!("Unsupported constructor {:?}", prop),
+            // The default constructor of a derived class passes its arguments
+            // on to the superclass
+            None if extends => {
+                codegen.instrs.push(Instruction::LoadArguments);
+                codegen.instrs.push(Instruction::SuperCallSpread);
+                codegen.instrs.push(Instruction::Pop);
+                0
+            }
+            None => 0,
+        };
+        let mut code = codegen.into_code();
+        code.class_constructor = true;
+        code.derived = extends;
+        let template = self.ctx.new_Function(
+            name.clone().unwrap_or_default(),
+            Rc::from(code),
+            length,
+            source,
+        );
+        self.consts.push(template);
+        self.instrs
+            .push(Instruction::MakeClass(self.consts.len() - 1, extends));
+        for prop in members {
+            let Prop {
+                key,
+                value,
+                kind,
+                computed,
+                is_static,
+                ..
+            } = prop;
+            let func = match value {
+                PropValue::Expr(Expr::Func(func)) => func,
+                value => panic!("Unsupported class member {:?}", value),
+            };
+            let key_name = match key {
+                PropKey::Expr(Expr::Ident(ref ident)) if !computed => ident.name.to_string(),
+                PropKey::Lit(Lit::String(StringLit::Double(ref s)))
+                | PropKey::Lit(Lit::String(StringLit::Single(ref s))) => {
+                    string_literal(s).to_string_lossy()
+                }
+                _ => String::new(),
+            };
+            self.visit_prop_key(key, computed);
+            let (name, instr) = match kind {
+                PropKind::Get => (
+                    format!("get {}", key_name),
+                    Instruction::ClassGetter(is_static),
+                ),
+                PropKind::Set => (
+                    format!("set {}", key_name),
+                    Instruction::ClassSetter(is_static),
+                ),
+                _ => (key_name, Instruction::ClassMethod(is_static)),
+            };
+            self.visit_method(func, name);
+            self.instrs.push(instr);
+        }
+        self.instrs.push(Instruction::Pop);
+        if let Some(ref name) = name {
+            let idx = self.name_index(name);
+            self.instrs.push(Instruction::Dup);
+            self.instrs.push(Instruction::InitBinding(idx));
+            self.leave_block();
+        }
+    }
+    // Pushes a method of a class, named after its key
+    fn visit_method(&mut self, func: Func, name: String) {
+        let source = match self.sources.claim_method(&func) {
+            Some(text) => text.into(),
+            None => "function () { [native code] }".into(),
+        };
+        let mut codegen = self.nested();
+        codegen.strict = true;
+        let length = codegen.visit_params(func.params);
+        codegen.visit_fnbody(func.body);
+        let mut code = codegen.into_code();
+        code.method = true;
+        let template = self.ctx.new_Function(name, Rc::from(code), length, source);
+        template
+            .unwrap_object()
+            .borrow_mut()
+            .props
+            .shift_remove(&JSString::from("prototype"));
+        self.consts.push(template);
+        self.instrs
+            .push(Instruction::MakeClosure(self.consts.len() - 1));
+    }
+    // Records the parameters of the function being compiled and emits the
+    // code giving them their default values and destructuring them. Returns
+    // the length of the function: the number of parameters before the first
+    // one with a default or the rest parameter.
+    fn visit_params(&mut self, params: Vec<FuncArg>) -> usize {
+        let count = params.len();
+        let mut length = None;
+        let mut initializers = Vec::new();
+        for (i, param) in params.into_iter().enumerate() {
+            let target = match param {
+                FuncArg::Pat(pat) => Target::from_pat(pat),
+                FuncArg::Expr(expr) => Target::from_expr(expr),
+            };
+            let (target, rest) = match target {
+                Target::Rest(target) => (*target, true),
+                target => (target, false),
+            };
+            if rest {
+                self.rest = Some(i);
+                self.simple_params = false;
+            }
+            if rest || matches!(target, Target::Default(..)) {
+                length.get_or_insert(i);
+            }
+            if let Target::Simple(Expr::Ident(ident)) = target {
+                self.index_of_param.insert(ident.name.to_string(), i);
+                continue;
+            }
+            self.simple_params = false;
+            // A pattern takes the slot of its argument under a name no
+            // identifier can have
+            let name = match target {
+                Target::Default(ref target, _) => match **target {
+                    Target::Simple(Expr::Ident(ref ident)) => ident.name.to_string(),
+                    _ => i.to_string(),
+                },
+                _ => i.to_string(),
+            };
+            self.index_of_param.insert(name, i);
+            initializers.push((i, target));
+        }
+        // The names bound by patterns get slots of their own, after those of
+        // the arguments
+        let mut names = Vec::new();
+        for (_, target) in initializers.iter() {
+            target.names(&mut names);
+        }
+        for name in names {
+            let slot = self.index_of_param.len();
+            self.index_of_param.entry(name).or_insert(slot);
+        }
+        // Defaults can refer to any parameter, so they come once all are known
+        for (idx, target) in initializers {
+            self.instrs.push(Instruction::LoadArg(idx));
+            self.visit_target(target, None);
+        }
+        length.unwrap_or(count)
+    }
+    // Pushes the callee of a call, before its arguments: the function, after
+    // its this value if it has one (ES6 12.3.4.1)
+    fn visit_callee(&mut self, callee: Expr) -> Callee {
+        match callee {
+            Expr::Super => Callee::Super,
+            // A method of the superclass is called with this
+            Expr::Member(MemberExpr {
+                object,
+                property,
+                computed,
+            }) if matches!(*object, Expr::Super) => {
+                self.visit_expr(Expr::This);
+                self.instrs.push(Instruction::Dup);
+                self.visit_property(*property, computed);
+                self.instrs.push(Instruction::LoadSuperProperty);
+                Callee::WithThis
+            }
+            // The base object of a member call is its this value
+            Expr::Member(MemberExpr {
+                object,
+                property,
+                computed,
+            }) => {
+                self.visit_expr(*object);
+                self.instrs.push(Instruction::Dup);
+                self.visit_property(*property, computed);
+                self.instrs.push(Instruction::LoadProperty);
+                Callee::WithThis
+            }
+            // A name bound by a with statement is called as a method
+            // of its object (ES5 10.2.1.2.6)
+            Expr::Ident(ident) if self.with_depth > 0 => {
+                self.visit_expr(Expr::Ident(ident));
+                match self.instrs.last() {
+                    Some(Instruction::LoadName(idx)) => {
+                        let idx = *idx;
+                        self.instrs
+                            .insert(self.instrs.len() - 1, Instruction::ImplicitThis(idx));
+                        Callee::WithThis
+                    }
+                    _ => Callee::Plain,
+                }
+            }
+            callee => {
+                self.visit_expr(callee);
+                Callee::Plain
+            }
+        }
+    }
+    // Pushes the arguments of a call and returns their number, or pushes an
+    // array of them when some are spread and returns None
+    fn visit_args(&mut self, args: Vec<Expr>) -> Option<usize> {
+        if args.iter().any(|arg| matches!(arg, Expr::Spread(_))) {
+            self.visit_elements(args.into_iter().map(Some).collect());
+            None
+        } else {
+            let len = args.len();
+            for arg in args {
+                self.visit_expr(arg);
+            }
+            Some(len)
+        }
+    }
+    // Calls a callee pushed by visit_callee with arguments pushed by
+    // visit_args
+    fn call(callee: Callee, len: Option<usize>) -> Instruction {
+        match (callee, len) {
+            (Callee::Plain, Some(len)) => Instruction::Call(len),
+            (Callee::Plain, None) => Instruction::CallSpread,
+            (Callee::WithThis, Some(len)) => Instruction::CallWithThis(len),
+            (Callee::WithThis, None) => Instruction::CallWithThisSpread,
+            (Callee::Super, Some(len)) => Instruction::SuperCall(len),
+            (Callee::Super, None) => Instruction::SuperCallSpread,
+        }
+    }
+    // Pushes an array of elements, appending those after the first spread
+    // one by one
+    fn visit_elements(&mut self, els: Vec<Option<Expr>>) {
+        // TODO: Empty array slots
+        let mut len = 0;
+        let mut spreading = false;
+        for el in els.into_iter().flatten() {
+            match el {
+                Expr::Spread(iterable) => {
+                    if !spreading {
+                        self.instrs.push(Instruction::MakeArray(len));
+                        spreading = true;
+                    }
+                    self.visit_expr(*iterable);
+                    self.instrs.push(Instruction::ArraySpread);
+                }
+                el => {
+                    self.visit_expr(el);
+                    if spreading {
+                        self.instrs.push(Instruction::ArrayPush);
+                    } else {
+                        len += 1;
+                    }
+                }
+            }
+        }
+        if !spreading {
+            self.instrs.push(Instruction::MakeArray(len));
+        }
+    }
+    fn name_index(&mut self, name: &str) -> usize {
+        match self.index_of_name.get(name) {
+            Some(idx) => *idx,
+            None => {
+                self.names.push(name.to_string());
+                self.index_of_name
+                    .insert(name.to_string(), self.names.len() - 1);
+                self.names.len() - 1
+            }
+        }
+    }
+    // Pushes the key of a member expression: the name itself for `o.name`
+    fn visit_property(&mut self, property: Expr, computed: bool) {
+        let prev = self.in_load_prop;
+        self.in_load_prop = !computed;
+        self.visit_expr(property);
+        self.in_load_prop = prev;
+    }
+    fn function_source(&self, func: &Func) -> JSString {
+        match self.sources.claim(func) {
+            Some(text) => text.into(),
+            None => "function () { [native code] }".into(),
+        }
+    }
+    // Pushes the key of a property in an object literal or pattern, converted
+    // to a string unless it is a plain name
+    fn visit_prop_key(&mut self, key: PropKey, computed: bool) {
+        match key {
+            PropKey::Lit(lit) => {
+                self.visit_expr(Expr::Lit(lit));
+                self.instrs.push(Instruction::ToString);
+            }
+            PropKey::Expr(expr) => {
+                self.visit_property(expr, computed);
+                if computed {
+                    self.instrs.push(Instruction::ToString);
+                }
+            }
+            PropKey::Pat(Pat::Ident(ident)) => self.visit_property(Expr::Ident(ident), computed),
+            PropKey::Pat(pat) => panic!("Unsupported property key {:?}", pat),
+        }
+    }
+    // Stores the value on top of the stack to a name or property
+    fn visit_store(&mut self, target: Expr) {
+        self.visit_expr(target);
+        let instr = match self.instrs.pop() {
+            Some(Instruction::LoadProperty) => Instruction::StoreProperty,
+            Some(Instruction::LoadName(idx)) => Instruction::StoreName(idx),
+            Some(Instruction::LoadArg(idx)) => Instruction::StoreArg(idx),
+            Some(instr) => instr,
+            None => panic!("Couldn't compute instr"),
+        };
+        self.instrs.push(instr);
+    }
+    // Binds the value on top of the stack to a pattern, declaring its names
+    // with `kind`, or assigning to them without
+    fn visit_pat(&mut self, pat: Pat, kind: Option<VarKind>) {
+        self.visit_target(Target::from_pat(pat), kind);
+    }
+    fn visit_target(&mut self, target: Target, kind: Option<VarKind>) {
+        match target {
+            Target::Simple(Expr::Ident(ident)) if kind.is_some() => {
+                let idx = self.name_index(&ident.name);
+                self.instrs.push(match kind {
+                    Some(VarKind::Var) => Instruction::StoreName(idx),
+                    _ => Instruction::InitBinding(idx),
+                });
+            }
+            Target::Simple(expr) => self.visit_store(expr),
+            Target::Default(target, default) => {
+                self.instrs.push(Instruction::Dup);
+                let jump = self.instrs.len();
+                self.instrs.push(Instruction::PopJumpIfDefined(0));
+                self.instrs.push(Instruction::Pop);
+                self.visit_expr(default);
+                self.instrs[jump] = Instruction::PopJumpIfDefined(self.instrs.len());
+                self.visit_target(*target, kind);
+            }
+            // Array patterns take the elements of an iterable (ES6 12.14.5.3)
+            Target::Array(elements) => {
+                self.instrs.push(Instruction::ToArray);
+                for (i, element) in elements.into_iter().enumerate() {
+                    match element {
+                        None => (),
+                        Some(Target::Rest(target)) => {
+                            self.instrs.push(Instruction::ArrayRest(i));
+                            self.visit_target(*target, kind);
+                        }
+                        Some(target) => {
+                            self.instrs.push(Instruction::Dup);
+                            self.consts.push((i as f64).into());
+                            self.instrs
+                                .push(Instruction::LoadConst(self.consts.len() - 1));
+                            self.instrs.push(Instruction::LoadProperty);
+                            self.visit_target(target, kind);
+                        }
+                    }
+                }
+                self.instrs.push(Instruction::Pop);
+            }
+            // The properties left over for a rest property are those whose
+            // keys were not loaded, noted in an array under the object
+            Target::Object(props, rest) => {
+                self.instrs.push(Instruction::CheckObjectCoercible);
+                if rest.is_some() {
+                    self.instrs.push(Instruction::MakeArray(0));
+                }
+                for (key, computed, target) in props {
+                    if rest.is_some() {
+                        self.visit_prop_key(key, computed);
+                        self.instrs.push(Instruction::LoadDestructured);
+                    } else {
+                        self.instrs.push(Instruction::Dup);
+                        self.visit_prop_key(key, computed);
+                        self.instrs.push(Instruction::LoadProperty);
+                    }
+                    self.visit_target(target, kind);
+                }
+                match rest {
+                    Some(rest) => {
+                        self.instrs.push(Instruction::ObjectRest);
+                        self.visit_target(*rest, kind);
+                    }
+                    None => self.instrs.push(Instruction::Pop),
+                }
+            }
+            Target::Rest(_) => panic!("Rest element must be last element"),
+        }
+    }
+    // A for-in or for-of statement, which steps through an array of the
+    // property names or elements, kept on the stack below the index of the
+    // next one
+    fn visit_for_in_of(&mut self, left: LoopLeft, right: Expr, body: Stmt, of: bool) {
+        self.visit_expr(right);
+        self.instrs.push(if of {
+            Instruction::ToArray
+        } else {
+            Instruction::ForInKeys
+        });
+        self.consts.push(0f64.into());
+        self.instrs
+            .push(Instruction::LoadConst(self.consts.len() - 1));
+        let start = self.instrs.len();
+        self.instrs.push(Instruction::IterNext(0));
+        let (kind, target) = match left {
+            LoopLeft::Variable(kind, VarDecl { id, .. }) => (Some(kind), Target::from_pat(id)),
+            LoopLeft::Pat(pat) => (None, Target::from_pat(pat)),
+            LoopLeft::Expr(expr) => (None, Target::from_expr(expr)),
+        };
+        self.push_target(TargetKind::Loop, 2);
+        // Each iteration binds let and const names in a scope of its own
+        let scoped = matches!(kind, Some(VarKind::Let) | Some(VarKind::Const));
+        if scoped {
+            let mut names = Vec::new();
+            target.names(&mut names);
+            let mutable = kind != Some(VarKind::Const);
+            self.enter_block(names.into_iter().map(|name| (name, mutable)).collect());
+        }
+        self.visit_target(target, kind);
+        self.visit_stmt(body);
+        if scoped {
+            self.leave_block();
+        }
+        // A continue has left the scope of the iteration already
+        self.patch_continues();
+        self.instrs.push(Instruction::Jump(start));
+        self.instrs[start] = Instruction::IterNext(self.instrs.len());
+        self.patch_breaks();
+        self.instrs.push(Instruction::Pop);
+        self.instrs.push(Instruction::Pop);
+    }
+}
+
+// A statement that break jumps out of, or for a loop, that continue jumps to
+// the next iteration of
+struct JumpTarget {
+    labels: Vec<String>,
+    kind: TargetKind,
+    // The scope depth outside it, and the number of values it keeps on the
+    // data stack
+    depth: usize,
+    values: usize,
+    // The jumps to patch once its end and its next iteration are known
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+// A break without a label leaves the innermost loop or switch, and a continue
+// without one goes on with the innermost loop
+#[derive(Clone, Copy, PartialEq)]
+enum TargetKind {
+    Loop,
+    Switch,
+    Labelled,
+}
+
+// What visit_callee pushed: a function, a this value and a function, or
+// nothing for the superclass constructor
+#[derive(Clone, Copy)]
+enum Callee {
+    Plain,
+    WithThis,
+    Super,
+}
+
+// The let and const names declared directly in a block, and whether they
+// are mutable
+fn lexical_names<'p, 'a: 'p>(
+    parts: impl IntoIterator<Item = &'p ProgramPart<'a>>,
+) -> Vec<(String, bool)> {
+    let mut names = Vec::new();
+    for part in parts {
+        match part {
+            ProgramPart::Decl(Decl::Var(kind, decls)) if *kind != VarKind::Var => {
+                names.extend(declared_names(*kind, decls));
+            }
+            ProgramPart::Decl(Decl::Class(Class { id: Some(id), .. })) => {
+                names.push((id.name.to_string(), true));
+            }
+            _ => (),
+        }
+    }
+    names
+}
+
+// The names declared with var or function statements in these parts, outside
+// of nested functions
+fn var_names<'p, 'a: 'p>(
+    parts: impl IntoIterator<Item = &'p ProgramPart<'a>>,
+    names: &mut Vec<String>,
+) {
+    for part in parts {
+        match part {
+            ProgramPart::Decl(Decl::Var(VarKind::Var, decls)) => {
+                names.extend(
+                    declared_names(VarKind::Var, decls)
+                        .into_iter()
+                        .map(|(name, _)| name),
+                );
+            }
+            ProgramPart::Decl(Decl::Func(Func { id: Some(id), .. })) => {
+                names.push(id.name.to_string());
+            }
+            ProgramPart::Stmt(stmt) => stmt_var_names(stmt, names),
+            _ => (),
+        }
+    }
+}
+
+fn stmt_var_names(stmt: &Stmt, names: &mut Vec<String>) {
+    match stmt {
+        Stmt::Block(BlockStmt(parts)) => var_names(parts, names),
+        Stmt::If(IfStmt {
+            consequent,
+            alternate,
+            ..
+        }) => {
+            stmt_var_names(consequent, names);
+            if let Some(alternate) = alternate {
+                stmt_var_names(alternate, names);
+            }
+        }
+        Stmt::Switch(SwitchStmt { cases, .. }) => {
+            var_names(cases.iter().flat_map(|c| c.consequent.iter()), names)
+        }
+        Stmt::For(ForStmt { init, body, .. }) => {
+            if let Some(LoopInit::Variable(VarKind::Var, decls)) = init {
+                names.extend(
+                    declared_names(VarKind::Var, decls)
+                        .into_iter()
+                        .map(|(name, _)| name),
+                );
+            }
+            stmt_var_names(body, names);
+        }
+        Stmt::ForIn(ForInStmt { left, body, .. }) | Stmt::ForOf(ForOfStmt { left, body, .. }) => {
+            if let LoopLeft::Variable(VarKind::Var, decl) = left {
+                Target::from_pat(decl.id.clone()).names(names);
+            }
+            stmt_var_names(body, names);
+        }
+        Stmt::While(WhileStmt { body, .. })
+        | Stmt::DoWhile(DoWhileStmt { body, .. })
+        | Stmt::With(WithStmt { body, .. })
+        | Stmt::Labeled(LabeledStmt { body, .. }) => stmt_var_names(body, names),
+        Stmt::Try(TryStmt {
+            block,
+            handler,
+            finalizer,
+        }) => {
+            var_names(&block.0, names);
+            if let Some(handler) = handler {
+                var_names(&handler.body.0, names);
+            }
+            if let Some(finalizer) = finalizer {
+                var_names(&finalizer.0, names);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn declared_names(kind: VarKind, decls: &[VarDecl]) -> Vec<(String, bool)> {
+    let mutable = kind != VarKind::Const;
+    let mut names = Vec::new();
+    for decl in decls {
+        Target::from_pat(decl.id.clone()).names(&mut names);
+    }
+    names.into_iter().map(|name| (name, mutable)).collect()
+}
+
+// What a value is bound or assigned to: a name or property, or a pattern
+// taking it apart. Declarations and parameters give patterns, while
+// assignments and for-in and for-of heads may give expressions that read
+// like them.
+enum Target<'a> {
+    Simple(Expr<'a>),
+    Default(Box<Target<'a>>, Expr<'a>),
+    Array(Vec<Option<Target<'a>>>),
+    Rest(Box<Target<'a>>),
+    // Properties by key and whether it is computed, and the rest property
+    Object(
+        Vec<(PropKey<'a>, bool, Target<'a>)>,
+        Option<Box<Target<'a>>>,
+    ),
+}
+
+impl<'a> Target<'a> {
+    fn from_pat(pat: Pat<'a>) -> Self {
+        match pat {
+            Pat::Ident(ident) => Target::Simple(Expr::Ident(ident)),
+            Pat::Assign(AssignPat { left, right }) => {
+                Target::Default(Box::new(Self::from_pat(*left)), *right)
+            }
+            Pat::RestElement(pat) => Target::Rest(Box::new(Self::from_pat(*pat))),
+            Pat::Array(parts) => Target::Array(
+                parts
+                    .into_iter()
+                    .map(|part| {
+                        part.map(|part| match part {
+                            ArrayPatPart::Pat(pat) => Self::from_pat(pat),
+                            ArrayPatPart::Expr(expr) => Self::from_expr(expr),
+                        })
+                    })
+                    .collect(),
+            ),
+            Pat::Obj(parts) => {
+                let mut props = Vec::new();
+                let mut rest = None;
+                for part in parts {
+                    match part {
+                        ObjPatPart::Assign(prop) => props.push(Self::from_prop(prop)),
+                        ObjPatPart::Rest(pat) => {
+                            let target = match Self::from_pat(*pat) {
+                                Target::Rest(target) => target,
+                                target => Box::new(target),
+                            };
+                            rest = Some(target);
+                        }
+                    }
+                }
+                Target::Object(props, rest)
+            }
+        }
+    }
+    fn from_expr(expr: Expr<'a>) -> Self {
+        match expr {
+            Expr::Assign(AssignExpr {
+                operator: AssignOp::Equal,
+                left,
+                right,
+            }) => {
+                let target = match left {
+                    AssignLeft::Pat(pat) => Self::from_pat(pat),
+                    AssignLeft::Expr(expr) => Self::from_expr(*expr),
+                };
+                Target::Default(Box::new(target), *right)
+            }
+            Expr::Spread(expr) => Target::Rest(Box::new(Self::from_expr(*expr))),
+            Expr::Array(elements) => Target::Array(
+                elements
+                    .into_iter()
+                    .map(|element| element.map(Self::from_expr))
+                    .collect(),
+            ),
+            Expr::Obj(parts) => {
+                let mut props = Vec::new();
+                let mut rest = None;
+                for part in parts {
+                    match part {
+                        ObjProp::Prop(prop) => props.push(Self::from_prop(prop)),
+                        ObjProp::Spread(expr) => rest = Some(Box::new(Self::from_expr(expr))),
+                    }
+                }
+                Target::Object(props, rest)
+            }
+            expr => Target::Simple(expr),
+        }
+    }
+    // A property of an object pattern; `{ a }` binds the name of its key
+    fn from_prop(prop: Prop<'a>) -> (PropKey<'a>, bool, Self) {
+        let target = match prop.value {
+            PropValue::Pat(pat) => Self::from_pat(pat),
+            PropValue::Expr(expr) => Self::from_expr(expr),
+            PropValue::None => match prop.key {
+                PropKey::Pat(Pat::Ident(ref ident)) => Target::Simple(Expr::Ident(ident.clone())),
+                ref key => panic!("Invalid destructuring target {:?}", key),
+            },
+        };
+        (prop.key, prop.computed, target)
+    }
+    // The names a declaration with this pattern binds
+    fn names(&self, names: &mut Vec<String>) {
+        match self {
+            Target::Simple(Expr::Ident(ident)) => names.push(ident.name.to_string()),
+            Target::Simple(_) => (),
+            Target::Default(target, _) | Target::Rest(target) => target.names(names),
+            Target::Array(elements) => {
+                for element in elements.iter().flatten() {
+                    element.names(names);
+                }
+            }
+            Target::Object(props, rest) => {
+                for (_, _, target) in props {
+                    target.names(names);
+                }
+                if let Some(rest) = rest {
+                    rest.names(names);
+                }
+            }
+        }
     }
 }
 
-pub fn gen_code<'a>(src: String, ctx: &'a Context) -> Code {
+// The cooked and raw strings of a part of a template literal, whose source
+// text runs from its opening ` or } to its closing ${ or `. The raw strings
+// keep escape sequences as written, with line terminators normalized to \n.
+fn template_strings(quasi: &TemplateElement) -> (Value, Value) {
+    let text = &quasi.raw[1..quasi.raw.len() - if quasi.tail { 1 } else { 2 }];
+    let raw = text.replace("\r\n", "\n").replace('\r', "\n");
+    (string_literal(&raw).into(), raw.into())
+}
+
+// Decodes the escape sequences of a string literal's source text into UTF-16
+fn string_literal(raw: &str) -> JSString {
+    let mut units = Vec::new();
+    let mut buf = [0u16; 2];
+    let mut chars = raw.chars().peekable();
+    let hex = |chars: &mut std::iter::Peekable<std::str::Chars>, n: usize| {
+        let digits: String = (0..n).filter_map(|_| chars.next()).collect();
+        u32::from_str_radix(&digits, 16).unwrap_or_default()
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            units.extend_from_slice(c.encode_utf16(&mut buf));
+            continue;
+        }
+        let c = match chars.next() {
+            Some(c) => c,
+            None => break,
+        };
+        match c {
+            'n' => units.push(0x0A),
+            't' => units.push(0x09),
+            'r' => units.push(0x0D),
+            'b' => units.push(0x08),
+            'f' => units.push(0x0C),
+            'v' => units.push(0x0B),
+            'x' => units.push(hex(&mut chars, 2) as u16),
+            'u' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let digits: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                let code_point = u32::from_str_radix(&digits, 16).unwrap_or_default();
+                match std::char::from_u32(code_point) {
+                    Some(c) => units.extend_from_slice(c.encode_utf16(&mut buf)),
+                    None => units.push(code_point as u16),
+                }
+            }
+            'u' => units.push(hex(&mut chars, 4) as u16),
+            '0'..='7' => {
+                // Legacy octal escapes, at most \377
+                let mut value = c.to_digit(8).unwrap_or_default();
+                let max_len = if c <= '3' { 3 } else { 2 };
+                for _ in 1..max_len {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            value = value * 8 + d;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                units.push(value as u16);
+            }
+            // Line continuations
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+            }
+            '\n' | '\u{2028}' | '\u{2029}' => (),
+            c => units.extend_from_slice(c.encode_utf16(&mut buf)),
+        }
+    }
+    units.into()
+}
+
+pub fn gen_code(src: String, ctx: &Context) -> Code {
     CodeGen::gen(src, ctx)
 }
+
+/// Compiles the source text of a single function expression, as the Function
+/// constructor does, into a function object. Fails with the parser's message
+/// if `src` is anything else.
+pub fn gen_function(src: &str, ctx: &Context) -> Result<Value, String> {
+    let wrapped = format!("({})", src);
+    parse_function(&wrapped, |func| {
+        let mut codegen = CodeGen::new(false, ctx, Rc::new(FunctionSources::new(&wrapped)));
+        codegen.visit_func(func);
+        codegen.consts.pop().expect("function not compiled")
+    })
+}
+
+/// Checks that `src` is the source text of a single function expression,
+/// without compiling it.
+pub fn check_function(src: &str) -> Result<(), String> {
+    parse_function(&format!("({})", src), |_| ())
+}
+
+fn parse_function<T>(wrapped: &str, f: impl FnOnce(Func) -> T) -> Result<T, String> {
+    let mut parser = Parser::new(wrapped).map_err(|e| e.to_string())?;
+    let program = parser.parse().map_err(|e| e.to_string())?;
+    match program {
+        Program::Script(mut parts) if parts.len() == 1 => match parts.pop() {
+            Some(ProgramPart::Stmt(Stmt::Expr(Expr::Func(func)))) => Ok(f(func)),
+            _ => Err("Unexpected token".to_string()),
+        },
+        _ => Err("Unexpected token".to_string()),
+    }
+}
@@ -1 +1,2 @@
 pub mod codegen;
+mod source;
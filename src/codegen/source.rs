@@ -0,0 +1,252 @@
+use resast::prelude::*;
+use ress::prelude::*;
+use ressa::Parser;
+
+use std::cell::RefCell;
+use std::ops::Range;
+
+/// The source text of every function in a script, for `Function.prototype.toString`.
+///
+/// The parser doesn't keep locations, so the candidate spans come from the token
+/// stream and each `Func`, `ArrowFuncExpr` or `Class` node claims the first unclaimed span
+/// that parses back to the same node, a class method as a member of a class. Code generation
+/// doesn't visit functions in source order (a class constructor is compiled before the methods
+/// preceding it, and parameter defaults after all the parameters), which rules out simple
+/// counting.
+pub struct FunctionSources {
+    src: String,
+    spans: Vec<Range<usize>>,
+    claimed: RefCell<Vec<bool>>,
+}
+
+impl FunctionSources {
+    pub fn new(src: &str) -> Self {
+        let tokens: Vec<_> = Scanner::new(src)
+            .filter_map(Result::ok)
+            .filter(|item| !item.token.is_comment())
+            .collect();
+        let is_punct = |k: usize, punct: Punct| match tokens.get(k) {
+            Some(Item {
+                token: Token::Punct(p),
+                ..
+            }) => *p == punct,
+            _ => false,
+        };
+        // Index of the token closing the bracket opened at `start`
+        let closing = |start: usize, open: Punct, close: Punct| {
+            let mut depth = 0;
+            for k in start..tokens.len() {
+                if is_punct(k, open) {
+                    depth += 1;
+                } else if is_punct(k, close) {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(k);
+                    }
+                }
+            }
+            None
+        };
+        // Index of the token opening the bracket closed at `end`
+        let opening = |end: usize, open: Punct, close: Punct| {
+            let mut depth = 0;
+            for k in (0..=end).rev() {
+                if is_punct(k, close) {
+                    depth += 1;
+                } else if is_punct(k, open) {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(k);
+                    }
+                }
+            }
+            None
+        };
+        // Index of the last token of the expression starting at `start`,
+        // which ends before a separator or closing bracket outside brackets
+        let expression_end = |start: usize| {
+            let mut depth = 0;
+            for k in start..tokens.len() {
+                if is_punct(k, Punct::OpenParen)
+                    || is_punct(k, Punct::OpenBracket)
+                    || is_punct(k, Punct::OpenBrace)
+                {
+                    depth += 1;
+                } else if is_punct(k, Punct::CloseParen)
+                    || is_punct(k, Punct::CloseBracket)
+                    || is_punct(k, Punct::CloseBrace)
+                {
+                    if depth == 0 {
+                        return k.checked_sub(1);
+                    }
+                    depth -= 1;
+                } else if depth == 0 && (is_punct(k, Punct::Comma) || is_punct(k, Punct::SemiColon))
+                {
+                    return k.checked_sub(1);
+                }
+            }
+            tokens.len().checked_sub(1)
+        };
+        let mut spans = Vec::new();
+        for (k, item) in tokens.iter().enumerate() {
+            if is_punct(k, Punct::EqualGreaterThan) && k > 0 {
+                let start = match tokens[k - 1].token {
+                    Token::Ident(_) => Some(k - 1),
+                    _ => opening(k - 1, Punct::OpenParen, Punct::CloseParen),
+                };
+                let end = if is_punct(k + 1, Punct::OpenBrace) {
+                    closing(k + 1, Punct::OpenBrace, Punct::CloseBrace)
+                } else {
+                    expression_end(k + 1)
+                };
+                if let (Some(start), Some(end)) = (start, end) {
+                    spans.push(tokens[start].span.start..tokens[end].span.end);
+                }
+                continue;
+            }
+            // A class runs to the brace closing the first one outside the
+            // parentheses of its heritage
+            if let Token::Keyword(Keyword::Class(_)) = item.token {
+                let mut body = k + 1;
+                while body < tokens.len() && !is_punct(body, Punct::OpenBrace) {
+                    body = match is_punct(body, Punct::OpenParen) {
+                        true => closing(body, Punct::OpenParen, Punct::CloseParen)
+                            .map_or(tokens.len(), |k| k + 1),
+                        false => body + 1,
+                    };
+                }
+                if let Some(end) = closing(body, Punct::OpenBrace, Punct::CloseBrace) {
+                    spans.push(item.span.start..tokens[end].span.end);
+                    // A method runs from its key, or the get or set before it,
+                    // to the brace closing its body
+                    let mut member = body + 1;
+                    while member < end {
+                        if is_punct(member, Punct::SemiColon) {
+                            member += 1;
+                            continue;
+                        }
+                        let start = match tokens[member].token {
+                            Token::Keyword(Keyword::Static(_))
+                                if !is_punct(member + 1, Punct::OpenParen) =>
+                            {
+                                member + 1
+                            }
+                            _ => member,
+                        };
+                        let mut params = start;
+                        while params < end && !is_punct(params, Punct::OpenParen) {
+                            params = match is_punct(params, Punct::OpenBracket) {
+                                true => closing(params, Punct::OpenBracket, Punct::CloseBracket)
+                                    .map_or(end, |k| k + 1),
+                                false => params + 1,
+                            };
+                        }
+                        let method_end = closing(params, Punct::OpenParen, Punct::CloseParen)
+                            .filter(|k| is_punct(k + 1, Punct::OpenBrace))
+                            .and_then(|k| closing(k + 1, Punct::OpenBrace, Punct::CloseBrace));
+                        match method_end {
+                            Some(method_end) if method_end < end => {
+                                spans.push(tokens[start].span.start..tokens[method_end].span.end);
+                                member = method_end + 1;
+                            }
+                            _ => break,
+                        }
+                    }
+                }
+                continue;
+            }
+            if let Token::Keyword(Keyword::Function(_)) = item.token {
+                let mut params = k + 1;
+                if is_punct(params, Punct::Asterisk) {
+                    params += 1;
+                }
+                if let Some(Token::Ident(_)) = tokens.get(params).map(|item| &item.token) {
+                    params += 1;
+                }
+                if !is_punct(params, Punct::OpenParen) {
+                    continue;
+                }
+                let end = closing(params, Punct::OpenParen, Punct::CloseParen)
+                    .filter(|k| is_punct(k + 1, Punct::OpenBrace))
+                    .and_then(|k| closing(k + 1, Punct::OpenBrace, Punct::CloseBrace));
+                if let Some(end) = end {
+                    spans.push(item.span.start..tokens[end].span.end);
+                }
+            }
+        }
+        FunctionSources {
+            src: src.to_string(),
+            claimed: RefCell::new(vec![false; spans.len()]),
+            spans,
+        }
+    }
+
+    pub fn claim(&self, func: &Func) -> Option<&str> {
+        self.claim_expr(|expr| matches!(expr, Expr::Func(parsed) if parsed == func))
+    }
+
+    pub fn claim_arrow(&self, arrow: &ArrowFuncExpr) -> Option<&str> {
+        self.claim_expr(|expr| matches!(expr, Expr::ArrowFunc(parsed) if parsed == arrow))
+    }
+
+    pub fn claim_class(&self, class: &Class) -> Option<&str> {
+        self.claim_expr(|expr| matches!(expr, Expr::Class(parsed) if parsed == class))
+    }
+
+    /// Claims the text of a class method, found by parsing it back as the
+    /// member of a class
+    pub fn claim_method(&self, method: &Func) -> Option<&str> {
+        self.claim_text(
+            |text| format!("(class {{ {} }})", text),
+            |expr| match expr {
+                Expr::Class(class) => class.body.0.iter().any(|prop| {
+                    matches!(prop.value, PropValue::Expr(Expr::Func(ref parsed)) if parsed == method)
+                }),
+                _ => false,
+            },
+        )
+    }
+
+    fn claim_expr(&self, is_node: impl Fn(&Expr) -> bool) -> Option<&str> {
+        self.claim_text(|text| format!("({})", text), is_node)
+    }
+
+    fn claim_text(
+        &self,
+        wrap: impl Fn(&str) -> String,
+        is_node: impl Fn(&Expr) -> bool,
+    ) -> Option<&str> {
+        let mut claimed = self.claimed.borrow_mut();
+        for (k, span) in self.spans.iter().enumerate() {
+            if claimed[k] {
+                continue;
+            }
+            let text = &self.src[span.clone()];
+            if parses_to(&wrap(text), &is_node) {
+                claimed[k] = true;
+                return Some(text);
+            }
+        }
+        None
+    }
+}
+
+// Whether `wrapped` is a script of a single expression statement matching
+// `is_node`
+fn parses_to(wrapped: &str, is_node: impl Fn(&Expr) -> bool) -> bool {
+    let mut parser = match Parser::new(wrapped) {
+        Ok(parser) => parser,
+        Err(_) => return false,
+    };
+    let program = match parser.parse() {
+        Ok(program) => program,
+        Err(_) => return false,
+    };
+    match program {
+        Program::Script(parts) => match parts.as_slice() {
+            [ProgramPart::Stmt(Stmt::Expr(expr))] => is_node(expr),
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -1,5 +1,6 @@
 mod vm;
 use vm::context::Context;
+use vm::options::Options;
 use vm::vm::VM;
 
 mod codegen;
@@ -23,6 +24,602 @@ fn test_new() {
     vm.run().unwrap();
 }
 
+// Host hooks that make a run repeatable: Math.random seeded, and the clock
+// frozen at 2016-03-01T10:30:00Z, five and a half hours ahead of UTC
+#[cfg(test)]
+fn test_options() -> Options {
+    Options {
+        clock: Box::new(|| 1456828200000.),
+        timezone: Box::new(|_| 19800000.),
+        ..Options::with_seed(42)
+    }
+}
+
+// Runs `source`, returning the lines it prints or the name of the error it
+// throws
+#[cfg(test)]
+fn run(source: &str, options: Options) -> Result<String, String> {
+    use objects::Objectable;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use vm::value::Value;
+    let printed = Rc::new(RefCell::new(String::new()));
+    let lines = printed.clone();
+    let options = Options {
+        output: Box::new(move |line| {
+            let mut lines = lines.borrow_mut();
+            lines.push_str(line);
+            lines.push('\n');
+        }),
+        ..options
+    };
+    let ctx = Context::new();
+    let mut vm = VM::with_options(gen_code(source.to_string(), &ctx), &ctx, options);
+    let result = match vm.run() {
+        Ok(_) => Ok(printed.borrow().clone()),
+        Err(Value::Object(ref e)) => Err(e.Get(&"name".into(), &mut vm).unwrap().to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    result
+}
+
+// What `source` prints, which must not throw
+#[cfg(test)]
+fn output(source: &str) -> String {
+    match run(source, test_options()) {
+        Ok(printed) => printed,
+        Err(name) => panic!("{} threw a {}", source, name),
+    }
+}
+
+// The name of the error `source` throws
+#[cfg(test)]
+fn thrown_error_name(source: &str) -> String {
+    match run(source, test_options()) {
+        Err(name) => name,
+        Ok(printed) => panic!("{} should throw an error, printed {:?}", source, printed),
+    }
+}
+
+// Checks that examples/`name`.js prints what examples/`name`.out holds
+#[cfg(test)]
+fn check_example(name: &str) {
+    let js = fs::read_to_string(format!("examples/{}.js", name)).expect("failed to read example");
+    let expected = fs::read_to_string(format!("examples/{}.out", name))
+        .expect("failed to read expected output");
+    assert_eq!(output(&js), expected, "output of examples/{}.js", name);
+}
+
+#[test]
+fn test_boolean() {
+    check_example("Boolean");
+    assert_eq!(
+        output("print(Boolean(new Boolean(false)), new Boolean(false).valueOf())"),
+        "true false\n"
+    );
+    assert_eq!(
+        thrown_error_name("Boolean.prototype.valueOf.call(1)"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("Boolean.prototype.toString.call(new Object())"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_math() {
+    check_example("Math");
+    assert_eq!(
+        output("print(Math.floor(0 - 1.5), Math.abs(0 - Infinity), Math.max(NaN, 1), Math.min(0, NaN))"),
+        "-2 Infinity NaN NaN\n"
+    );
+    assert_eq!(thrown_error_name("Math()"), "TypeError");
+    assert_eq!(thrown_error_name("new Math.abs(1)"), "TypeError");
+}
+
+#[test]
+fn test_string_methods() {
+    check_example("String_methods");
+    assert_eq!(
+        output("print('ab'.repeat(0) + '|', 'x'.padEnd(0), 'abc'.padStart(6, ''))"),
+        "| x abc\n"
+    );
+    assert_eq!(
+        output("print(String.prototype.toLowerCase.call(1e21), String.prototype.trim.call(12))"),
+        "1e+21 12\n"
+    );
+    assert_eq!(thrown_error_name("'a'.repeat(1e12)"), "RangeError");
+    assert_eq!(thrown_error_name("'a'.repeat(0 - 1)"), "RangeError");
+    assert_eq!(thrown_error_name("''.padStart(1e12, 'x')"), "RangeError");
+    assert_eq!(
+        thrown_error_name("String.prototype.toString.call(null)"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("String.prototype.valueOf.call({})"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("String.prototype.toUpperCase.call(undefined)"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_number_format() {
+    check_example("Number_format");
+    assert_eq!(
+        output("print((0.5).toFixed(0), (1.255).toFixed(2), (1e-7).toString(), (123.456).toString(36))"),
+        "1 1.25 1e-7 3f.gez4w97ry\n"
+    );
+    assert_eq!(thrown_error_name("(1).toFixed(101)"), "RangeError");
+    assert_eq!(thrown_error_name("(1).toString(37)"), "RangeError");
+    assert_eq!(thrown_error_name("(1).toPrecision(0)"), "RangeError");
+    assert_eq!(
+        thrown_error_name("Number.prototype.toFixed.call('1')"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_global_functions() {
+    check_example("global");
+    assert_eq!(
+        output("print(parseInt('0x'), parseInt('12', 37), isNaN(NaN), new RangeError('r') + '')"),
+        "NaN NaN true RangeError: r\n"
+    );
+    for source in &[
+        "decodeURI('%')",
+        "decodeURIComponent('%C0%80')",
+        "encodeURI('\\uD800')",
+    ] {
+        assert_eq!(thrown_error_name(source), "URIError");
+    }
+}
+
+#[test]
+fn test_global_object() {
+    check_example("global_object");
+    assert_eq!(
+        output("var v = 1; function g() { return this; } print(g() == globalThis, globalThis.v, w = 2, globalThis.w)"),
+        "true 1 2 2\n"
+    );
+    // Var and function declarations are hoisted
+    assert_eq!(
+        output("print(this.hasOwnProperty('y'), x == undefined, '' + x); var y; var x = 1; var print; print(x)"),
+        "true true undefined\n1\n"
+    );
+    assert_eq!(
+        output("function f() { return g(); function g() { return 'g'; } } print(f(), h()); function h() { return 'h'; } var h;"),
+        "g h\n"
+    );
+    assert_eq!(thrown_error_name("print(notDefined)"), "ReferenceError");
+}
+
+#[test]
+fn test_function_methods() {
+    check_example("Function_methods");
+    // Strict functions take this as given, others box primitives and replace
+    // null with the global object
+    assert_eq!(
+        output("function f(a) { 'use strict'; return [this, a]; } var r = f.call(5, 6); print(r[0], r[1], f.bind(1).bind(2)()[0])"),
+        "5 6 1\n"
+    );
+    assert_eq!(
+        output("function g() { return this; } print(g.call(5) instanceof Number, g.call(null) == globalThis)"),
+        "true true\n"
+    );
+    assert_eq!(
+        thrown_error_name("function f() {} f.apply(null, 1)"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("var o = new Object(); o.call = Function.prototype.call; o.call()"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_function_constructor() {
+    check_example("Function_constructor");
+    assert_eq!(
+        output("var f = Function('a = 1', '...rest', 'return a + rest.length'); print(f(), f(5, 6, 7), f.length)"),
+        "1 7 0\n"
+    );
+    assert_eq!(
+        output("var g = Function('var x = 3; return x'); print(g(), Function('return this')() == globalThis)"),
+        "3 true\n"
+    );
+    assert_eq!(thrown_error_name("new Function('return (')"), "SyntaxError");
+    assert_eq!(
+        thrown_error_name("Function('}); print(1); (function () {')"),
+        "SyntaxError"
+    );
+    assert_eq!(thrown_error_name("Function('/*', '*/){')"), "SyntaxError");
+    assert_eq!(
+        thrown_error_name("Function('a', '}, function () {')"),
+        "SyntaxError"
+    );
+}
+
+#[test]
+fn test_arguments() {
+    check_example("arguments");
+    // Only sloppy functions with simple parameters map arguments to them
+    assert_eq!(
+        output("function f(a) { a = 2; return arguments[0]; } function g(a = 0) { a = 2; return arguments[0]; } function h(a) { 'use strict'; a = 2; return arguments[0]; } print(f(1), g(1), h(1))"),
+        "2 1 1\n"
+    );
+    assert_eq!(
+        output("print((function () { return arguments.length; })(undefined, undefined))"),
+        "2\n"
+    );
+}
+
+#[test]
+fn test_this() {
+    check_example("this");
+    // The callee and its this value are evaluated before the arguments
+    assert_eq!(
+        output("var first = { name: 'first', m: function (x) { return this.name + ':' + x.name; } }; var second = { name: 'second' }; print(first.m(first = second))"),
+        "first:second\n"
+    );
+    assert_eq!(
+        output("var log = ''; function outer() { log = log + 'outer '; return function (x) { return log + x; }; } function inner() { log = log + 'inner '; return 'done'; } print(outer()(inner()))"),
+        "outer inner done\n"
+    );
+    for source in &[
+        "var o = {}; o.missing()",
+        "var o = {x: 1}; o.x()",
+        "var f = 'f'; f()",
+        "new 1",
+        "new Math.max()",
+        "var u; u.x",
+        "var u = null; u.x = 1",
+    ] {
+        assert_eq!(thrown_error_name(source), "TypeError");
+    }
+}
+
+#[test]
+fn test_object_static_methods() {
+    check_example("Object_static");
+    // Integer keys come first in ascending order, then the others as added
+    assert_eq!(
+        output("var o = { b: 1, a: 2, 10: 'ten', 2: 'two' }; o.c = 3; var keys = ''; for (var k in o) { keys = keys + k + ' '; } var { a, ...rest } = o; print(Object.keys(o).join(), keys, Object.keys(rest).join())"),
+        "2,10,b,a,c 2 10 b a c  2,10,b,c\n"
+    );
+    assert_eq!(
+        output("print(Object.entries({ y: 1, x: 2 }).join(';'), Object.keys(Object.assign({ z: 0 }, { y: 1, z: 2 })).join())"),
+        "y,1;x,2 z,y\n"
+    );
+    // String objects have an index property per code unit
+    assert_eq!(
+        output("var s = new String('ab'); s.x = 1; s[5] = 2; print(Object.keys(s).join(), Object.getOwnPropertyNames('ab').join(), s[0], 'ab'[1], s.hasOwnProperty('1'), s.propertyIsEnumerable(0))"),
+        "0,1,5,x 0,1,length a b true true\n"
+    );
+    assert_eq!(
+        output("var s = new String('ab'); s[0] = 'z'; var k = ''; for (var i in s) { k = k + i; } var d = Object.getOwnPropertyDescriptor(s, '1'); Object.defineProperty(s, '0', { value: 'a' }); print(s[0], k, d.value, d.writable, d.enumerable, d.configurable, Object.keys(s).join())"),
+        "a 01 b false true false 0,1\n"
+    );
+    assert_eq!(
+        thrown_error_name("Object.defineProperty(new String('a'), '0', { value: 'b' })"),
+        "TypeError"
+    );
+    assert_eq!(thrown_error_name("Object.keys(null)"), "TypeError");
+    assert_eq!(thrown_error_name("Object.create(1)"), "TypeError");
+    assert_eq!(
+        thrown_error_name("Object.defineProperty(Object.freeze(new Object()), 'a', new Object())"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("var a = new Object(); Object.setPrototypeOf(a, Object.create(a))"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_object_prototype_methods() {
+    check_example("Object_prototype");
+    assert_eq!(
+        output("print(Object.prototype.toString.call([]), Object.prototype.toString.call(null), Object.prototype.toString.call(new Date(0)), {}.toString())"),
+        "[object Array] [object Null] [object Date] [object Object]\n"
+    );
+    assert_eq!(
+        output("print(Object.prototype.toString.call(Math), '' + Math)"),
+        "[object Math] [object Math]\n"
+    );
+    assert_eq!(
+        output("print({ a: 1 }.hasOwnProperty('a'), {}.hasOwnProperty('toString'), Object.prototype.isPrototypeOf({}), [].propertyIsEnumerable('length'))"),
+        "true false true false\n"
+    );
+    assert_eq!(
+        thrown_error_name("Object.prototype.hasOwnProperty.call(null, 'a')"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_accessors() {
+    check_example("accessors");
+    assert_eq!(
+        output("var o = {}; var v = 0; Object.defineProperty(o, 'x', { get: function () { return v + 1; }, set: function (n) { v = n; }, enumerable: true }); o.x = 5; print(o.x, v, Object.keys(o).join())"),
+        "6 5 x\n"
+    );
+    assert_eq!(
+        output("var o = { get y() { return 2; } }; o.y = 3; print(o.y, Object.getOwnPropertyDescriptor(o, 'y').set == undefined)"),
+        "2 true\n"
+    );
+    assert_eq!(
+        thrown_error_name("Object.defineProperty({}, 'a', { get: 1 })"),
+        "TypeError"
+    );
+    assert_eq!(
+        thrown_error_name("Object.defineProperty({}, 'a', { get: function() {}, value: 1 })"),
+        "TypeError"
+    );
+}
+
+#[test]
+fn test_instanceof_and_in() {
+    check_example("instanceof_in");
+    assert_eq!(
+        output("function A() {} var a = new A(); print(a instanceof A, a instanceof Object, 'x' in { x: undefined }, 'toString' in {}, 0 in [1], 1 in [1])"),
+        "true true true true true false\n"
+    );
+    assert_eq!(thrown_error_name("({}) instanceof {}"), "TypeError");
+    assert_eq!(thrown_error_name("'a' in 'abc'"), "TypeError");
+}
+
+#[test]
+fn test_switch() {
+    check_example("switch");
+    assert_eq!(
+        output("function s(x) { switch (x) { case 1: return 'one'; case '1': return 'string'; default: return 'other'; } } print(s(1), s('1'), s(true))"),
+        "one string other\n"
+    );
+    assert_eq!(
+        output("var r = ''; switch (3) { default: r = r + 'd'; case 1: r = r + '1'; break; case 2: r = r + '2'; } print(r)"),
+        "d1\n"
+    );
+}
+
+#[test]
+fn test_with_and_sequence() {
+    check_example("with_sequence");
+    assert_eq!(
+        output("var x = (1, 2, 3); var o = { p: 1 }; with (o) { p = 2; q = 3; } print(x, o.p, o.q == undefined, q)"),
+        "3 2 true 3\n"
+    );
+    // A var in a with body at the top level is declared on the global object,
+    // but its initializer assigns to the with object
+    assert_eq!(
+        output(
+            "var o = {x: 1}; with (o) { var x = 5; } print(o.x, x == undefined, 'x' in globalThis)"
+        ),
+        "5 true true\n"
+    );
+    assert_eq!(thrown_error_name("with (null) {}"), "TypeError");
+}
+
+#[test]
+fn test_let_and_const() {
+    check_example("let_const");
+    assert_eq!(
+        output("var s = ''; for (let i = 0; i - 4; i = i + 1) { if (i == 1) continue; s = s + i; } print(s)"),
+        "023\n"
+    );
+    assert_eq!(
+        output("var n = 0; outer: for (var i = 0; i - 3; i = i + 1) { for (var j = 0; j - 3; j = j + 1) { if (j == 1) continue outer; if (i == 2) break outer; n = n + 1; } } print(n, i)"),
+        "2 2\n"
+    );
+    assert_eq!(
+        output("function f(a) { var a; var b = a + 1; return b; } var b = 10; print(f(1), b)"),
+        "2 10\n"
+    );
+    assert_eq!(thrown_error_name("{ x = 2; let x; }"), "ReferenceError");
+    assert_eq!(
+        thrown_error_name("function f() { return z; } f(); let z = 1;"),
+        "ReferenceError"
+    );
+    assert_eq!(thrown_error_name("const c = 1; c = 2;"), "TypeError");
+    assert_eq!(
+        thrown_error_name("function f() { var x = 1; } f(); x"),
+        "ReferenceError"
+    );
+}
+
+#[test]
+fn test_arrow_functions() {
+    check_example("arrow_functions");
+    assert_eq!(
+        output("var o = { n: 3, f: function () { var g = x => x + this.n; return g(1); } }; print(o.f())"),
+        "4\n"
+    );
+    assert_eq!(
+        output("var f = (a, b) => ({ s: a + b }); print(f(1, 2).s, (() => {})() == undefined)"),
+        "3 true\n"
+    );
+    assert_eq!(thrown_error_name("var f = () => 1; new f()"), "TypeError");
+}
+
+#[test]
+fn test_default_rest_and_spread() {
+    check_example("default_rest_spread");
+    assert_eq!(
+        output("function f(a, b = a + 1, ...r) { return [a, b, r.length].join(); } print(f(1), f(1, 5, 7, 8))"),
+        "1,2,0 1,5,2\n"
+    );
+    assert_eq!(
+        output("function g() { return arguments.length; } var a = [1, 2]; print(g(...a, 3, ...'xy'), [0, ...a, ...[]].join())"),
+        "5 0,1,2\n"
+    );
+    // With defaults or a rest parameter, a var of a parameter's name is a
+    // binding of its own, starting with the parameter's value
+    assert_eq!(
+        output("function f(a, b = () => a) { var a = 3; return [a, b()].join(); } function g(a = 1) { var a; return a; } function h({ x }, y = 2) { var x, y; x = x + y; return x; } function k(a) { var a; return a; } print(f(1), g(5), g(), h({ x: 1 }), k(4))"),
+        "3,1 5 1 3 4\n"
+    );
+    assert_eq!(thrown_error_name("function f() {} f(...1)"), "TypeError");
+    assert_eq!(thrown_error_name("[...null]"), "TypeError");
+}
+
+#[test]
+fn test_destructuring() {
+    check_example("destructuring");
+    assert_eq!(
+        output("var { a, b: [c, d = 4], e = 5 } = { a: 1, b: [3] }; print(a, c, d, e)"),
+        "1 3 4 5\n"
+    );
+    assert_eq!(
+        output("var s = ''; for (var [k, v] of [['x', 1], ['y', 2]]) { s = s + k + v; } var [p, ...q] = 'abc'; print(s, p, q.join())"),
+        "x1y2 a b,c\n"
+    );
+    assert_eq!(thrown_error_name("var { a } = null"), "TypeError");
+    assert_eq!(thrown_error_name("var [a] = 1"), "TypeError");
+    assert_eq!(thrown_error_name("const [c] = [1]; [c] = [2]"), "TypeError");
+}
+
+#[test]
+fn test_templates() {
+    check_example("templates");
+    assert_eq!(
+        output("var a = 2, o = { n: 'x' }; print(`${a + 1}-${o.n}${`in${a}`}`)"),
+        "3-xin2\n"
+    );
+    assert_eq!(
+        output(
+            r"print(`a
+b`.length, `A${''}\n`.length)"
+        ),
+        "3 2\n"
+    );
+}
+
+#[test]
+fn test_classes() {
+    check_example("classes");
+    assert_eq!(
+        output("class A { constructor(x) { this.x = x; } get d() { return this.x + this.x; } static make() { return new A(4); } } print(A.make().d, A.prototype.constructor == A)"),
+        "8 true\n"
+    );
+    assert_eq!(
+        output("class A { constructor(x) { this.x = x; } m() { return 'A' + this.x; } } class B extends A { constructor() { super(1); } m() { return 'B' + super.m(); } } var b = new B(); print(b.m(), b instanceof A, Object.keys(A.prototype).length)"),
+        "BA1 true 0\n"
+    );
+    // Methods keep their source text and aren't constructors
+    assert_eq!(
+        output("class A { m(a) { return a; } static get g() { return 1; } ['x' + 1]() {} } print(A.prototype.m.toString(), Object.getOwnPropertyDescriptor(A, 'g').get.toString(), A.prototype.x1.toString())"),
+        "m(a) { return a; } get g() { return 1; } ['x' + 1]() {}\n"
+    );
+    assert_eq!(
+        thrown_error_name("class A { m() {} } new A.prototype.m()"),
+        "TypeError"
+    );
+    assert_eq!(thrown_error_name("class A {} A()"), "TypeError");
+    assert_eq!(thrown_error_name("class A extends 1 {}"), "TypeError");
+    assert_eq!(
+        thrown_error_name("class A extends Object { constructor() { this.a = 1; } } new A()"),
+        "ReferenceError"
+    );
+}
+
+#[test]
+fn test_utf16_strings() {
+    check_example("unicode");
+    assert_eq!(
+        output(
+            r"var s = '\u{1F600}'; print(s.length, s.charCodeAt(0), s.charCodeAt(1), s.slice(1) == '\uDE00')"
+        ),
+        "2 55357 56832 true\n"
+    );
+    // Property keys keep lone surrogates
+    assert_eq!(
+        output(
+            r"var o = {}; o['\uD800'] = 1; o['\uDBFF'] = 2; var k = Object.keys(o); print(k.length, k[0].charCodeAt(0), k[1].charCodeAt(0), o['\uD800'], o[k[1]])"
+        ),
+        "2 55296 56319 1 2\n"
+    );
+    assert_eq!(
+        output(
+            r"var s = ''; for (var k in { '\uDC00': 1, a: 2 }) { s = s + k.charCodeAt(0) + ' '; } print(s + ('\uDC00' in { '\uDC00': 1 }) + ' ' + ({ '\uDC00': 1 }).hasOwnProperty('\uDFFF'))"
+        ),
+        "56320 97 true false\n"
+    );
+
+    use vm::jsstring::JSString;
+    let lone = JSString::from(vec![0xD800, 0x61]);
+    assert_eq!(lone.len(), 2);
+    assert!(lone.to_rust_string().is_err());
+    assert_eq!(lone.to_string_lossy(), "\u{FFFD}a");
+    let s = JSString::from("😀");
+    assert_eq!(s.units(), &[0xD83D, 0xDE00]);
+    assert_eq!(s.to_rust_string().unwrap(), "😀");
+}
+
+#[test]
+fn test_seeded_random() {
+    use vm::random::Random;
+    let mut r1 = Random::from_seed(42);
+    let mut r2 = Random::from_seed(42);
+    for _ in 0..1000 {
+        let n = r1.next_f64();
+        assert_eq!(n, r2.next_f64());
+        assert!((0. ..1.).contains(&n));
+    }
+    assert_ne!(
+        Random::from_seed(1).next_f64(),
+        Random::from_seed(2).next_f64()
+    );
+
+    // The same seed gives the same numbers to scripts
+    let source = "print(Math.random(), Math.random())";
+    let first = run(source, Options::with_seed(7)).unwrap();
+    assert_eq!(first, run(source, Options::with_seed(7)).unwrap());
+    assert_ne!(first, run(source, Options::with_seed(8)).unwrap());
+
+    // Even all ones from the entropy source stay below 1
+    let options = Options {
+        random: Random::from_entropy(Box::new(|| u64::MAX)),
+        ..Options::default()
+    };
+    let n: f64 = run("print(Math.random())", options)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    assert!((0. ..1.).contains(&n));
+}
+
+#[test]
+fn test_date() {
+    check_example("Date");
+
+    // Fields before 1970 are never -0, which atan2 tells apart from 0
+    let d = "var d = new Date(0 - 86400000);";
+    assert_eq!(
+        output(&format!(
+            "{} print(Math.atan2(0, d.getUTCHours()), Math.atan2(0, d.getUTCMilliseconds()))",
+            d
+        )),
+        "0 0\n"
+    );
+    assert_eq!(
+        output("var d = new Date(0 - 1); print(d.getUTCHours(), d.getUTCMinutes(), d.getUTCSeconds(), d.getUTCMilliseconds())"),
+        "23 59 59 999\n"
+    );
+    // Negative years have four digits in strings, and six in ISO strings
+    assert_eq!(
+        output("var d = new Date(Date.UTC(0 - 50, 0, 1)); print(d.toUTCString()); print(d.toISOString())"),
+        "Sun, 01 Jan -0050 00:00:00 GMT\n-000050-01-01T00:00:00.000Z\n"
+    );
+    assert_eq!(
+        run("print(new Date(0).toString())", Options::default()),
+        Ok("Thu Jan 01 1970 00:00:00 GMT+0000\n".to_string())
+    );
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     let filename = match args.len() {
@@ -33,8 +630,16 @@ fn main() {
         Ok(js) => js,
         Err(msg) => panic!("{}", msg.to_string()),
     };
+    let options = match env::var("RJS_SEED") {
+        Ok(seed) => Options::with_seed(seed.parse().expect("RJS_SEED must be an integer")),
+        Err(_) => Options::default(),
+    };
     let ctx = Context::new();
     let code = gen_code(js, &ctx);
-    let mut vm = VM::new(code, &ctx);
-    vm.run().unwrap();
+    let mut vm = VM::with_options(code, &ctx, options);
+    if let Err(e) = vm.run() {
+        let message = e.ToString(&mut vm).unwrap_or(e);
+        eprintln!("Uncaught {}", message);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,113 @@
+use super::error::ErrorKind;
+use super::*;
+
+#[derive(Trace, Finalize, Debug)]
+pub struct Array;
+
+// An array index is a canonical numeric string below 2^32 - 1
+pub fn array_index(key: &JSString) -> Option<u32> {
+    let key = key.to_rust_string().ok()?;
+    match key.parse::<u32>() {
+        Ok(idx) if idx != u32::MAX && idx.to_string() == key => Some(idx),
+        _ => None,
+    }
+}
+
+fn length_of(array: &Object) -> u32 {
+    match array.props.get(&JSString::from("length")) {
+        Some(Property {
+            value: Value::Number(n),
+            ..
+        }) => *n as u32,
+        _ => 0,
+    }
+}
+
+// [[Put]] for arrays keeps `length` in sync with the indexed properties
+pub fn Put(array: &mut Object, key: JSString, value: Value) {
+    if key == "length" {
+        let new_len = match value {
+            Value::Number(n) => n as u32,
+            _ => return,
+        };
+        array
+            .props
+            .retain(|key, _| !matches!(array_index(key), Some(idx) if idx >= new_len));
+        if let Some(prop) = array.props.get_mut(&JSString::from("length")) {
+            prop.value = (new_len as f64).into();
+        }
+        return;
+    }
+    if let Some(idx) = array_index(&key) {
+        if idx >= length_of(array) {
+            if let Some(prop) = array.props.get_mut(&JSString::from("length")) {
+                prop.value = (idx as f64 + 1.).into();
+            }
+        }
+    }
+    match array.props.get_mut(&key) {
+        Some(prop) => prop.value = value,
+        None => {
+            array.props.insert(key, Property::new(value));
+        }
+    }
+}
+
+// JS Primitives
+pub fn function(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    constructor(vm, this, args)
+}
+
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    if let [Value::Number(len)] = args {
+        if *len < 0. || len.fract() != 0. || *len >= u32::MAX as f64 {
+            return Err(vm
+                .ctx
+                .new_Error(ErrorKind::RangeError, "Invalid array length"));
+        }
+        let array = vm.ctx.new_Array(Vec::new());
+        array
+            .unwrap_object()
+            .Put("length".into(), (*len).into(), vm)?;
+        return Ok(array);
+    }
+    Ok(vm.ctx.new_Array(args.to_vec()))
+}
+
+fn join(vm: &mut VM, this: &Value, separator: &str) -> JSResult {
+    let this = this.as_object(vm.ctx);
+    let len = match this.Get(&"length".into(), vm)? {
+        Value::Number(n) => n as u32,
+        _ => 0,
+    };
+    let mut res = std::string::String::new();
+    for idx in 0..len {
+        if idx > 0 {
+            res.push_str(separator);
+        }
+        let el = this.Get(&idx.to_string().into(), vm)?;
+        match el {
+            Value::Undefined | Value::Null => (),
+            _ => res.push_str(&el.ToString(vm)?.unwrap_string().to_string_lossy()),
+        }
+    }
+    Ok(res.into())
+}
+
+use crate::js_impl;
+use crate::vm::context::Context;
+
+js_impl! {
+    #[prop(name=join, length=1)]
+    fn join(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let separator = match args.first() {
+            None | Some(Value::Undefined) => ",".into(),
+            Some(sep) => sep.ToString(vm)?,
+        };
+        self::join(vm, this, &separator.unwrap_string().to_string_lossy())
+    },
+    #[prop(name=toString, length=0)]
+    fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        self::join(vm, this, ",")
+    }
+}
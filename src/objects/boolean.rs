@@ -0,0 +1,63 @@
+use super::error::ErrorKind;
+use super::*;
+
+#[derive(Trace, Finalize, Debug, Clone)]
+pub struct Boolean {
+    value: bool,
+}
+impl Boolean {
+    pub fn new(value: bool) -> Self {
+        Self { value }
+    }
+    pub fn valueOf(&self) -> Value {
+        self.value.into()
+    }
+    pub fn toString(&self) -> Value {
+        self.value.to_string().into()
+    }
+}
+
+// thisBooleanValue (ES6 19.3.3)
+fn this_boolean(vm: &VM, this: &Value) -> Result<Boolean, Value> {
+    match this {
+        Value::Boolean(b) => Ok(Boolean::new(*b)),
+        Value::Object(o) => match o.borrow().payload {
+            ObjectPayload::Boolean(ref b) => Ok(b.clone()),
+            _ => Err(vm
+                .ctx
+                .new_Error(ErrorKind::TypeError, "`this` is not a Boolean")),
+        },
+        _ => Err(vm
+            .ctx
+            .new_Error(ErrorKind::TypeError, "`this` is not a Boolean")),
+    }
+}
+
+// JS Primitives
+pub fn function(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    Ok(match args.len() {
+        0 => Value::Boolean(false),
+        _ => args[0].ToBoolean(),
+    })
+}
+
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    match args.len() {
+        0 => Ok(vm.ctx.new_Boolean(false)),
+        _ => Ok(vm.ctx.new_Boolean(args[0].clone().into())),
+    }
+}
+
+use crate::js_impl;
+use crate::vm::context::Context;
+
+js_impl! {
+    #[prop(name=valueOf,length=0)]
+    fn valueOf(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(this_boolean(vm, this)?.valueOf())
+    },
+    #[prop(name=toString, length=0)]
+    fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(this_boolean(vm, this)?.toString())
+    }
+}
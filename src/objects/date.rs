@@ -0,0 +1,815 @@
+use super::error::ErrorKind;
+use super::*;
+
+#[derive(Trace, Finalize, Debug, Clone)]
+pub struct Date {
+    value: f64,
+}
+impl Date {
+    pub fn new(value: f64) -> Self {
+        Self { value }
+    }
+    pub fn valueOf(&self) -> Value {
+        self.value.into()
+    }
+    pub fn toString(&self, vm: &VM) -> Value {
+        to_string(self.value, vm).into()
+    }
+}
+
+// Time values (ECMA-262 15.9.1)
+const MS_PER_SECOND: f64 = 1000.;
+const MS_PER_MINUTE: f64 = 60000.;
+const MS_PER_HOUR: f64 = 3600000.;
+const MS_PER_DAY: f64 = 86400000.;
+
+const WEEK_DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// The remainder with the sign of y, and never -0
+fn modulo(x: f64, y: f64) -> f64 {
+    let r = x % y;
+    if r < 0. {
+        r + y
+    } else {
+        r + 0.
+    }
+}
+
+fn ToInteger(n: f64) -> f64 {
+    if n.is_nan() {
+        0.
+    } else {
+        n.trunc()
+    }
+}
+
+fn Day(t: f64) -> f64 {
+    (t / MS_PER_DAY).floor()
+}
+
+fn DaysInYear(y: f64) -> f64 {
+    if y % 4. != 0. || (y % 100. == 0. && y % 400. != 0.) {
+        365.
+    } else {
+        366.
+    }
+}
+
+fn DayFromYear(y: f64) -> f64 {
+    365. * (y - 1970.) + ((y - 1969.) / 4.).floor() - ((y - 1901.) / 100.).floor()
+        + ((y - 1601.) / 400.).floor()
+}
+
+fn TimeFromYear(y: f64) -> f64 {
+    MS_PER_DAY * DayFromYear(y)
+}
+
+fn YearFromTime(t: f64) -> f64 {
+    let mut y = (Day(t) / 365.2425).floor() + 1970.;
+    while TimeFromYear(y) > t {
+        y -= 1.;
+    }
+    while TimeFromYear(y + 1.) <= t {
+        y += 1.;
+    }
+    y
+}
+
+fn InLeapYear(t: f64) -> f64 {
+    DaysInYear(YearFromTime(t)) - 365.
+}
+
+fn DayWithinYear(t: f64) -> f64 {
+    Day(t) - DayFromYear(YearFromTime(t))
+}
+
+// Day of the year on which each month starts, in a common year
+fn MonthStart(m: usize, leap: f64) -> f64 {
+    const START: [f64; 13] = [
+        0., 31., 59., 90., 120., 151., 181., 212., 243., 273., 304., 334., 365.,
+    ];
+    START[m] + if m >= 2 { leap } else { 0. }
+}
+
+fn MonthFromTime(t: f64) -> f64 {
+    let day = DayWithinYear(t);
+    let leap = InLeapYear(t);
+    let mut m = 0;
+    while day >= MonthStart(m + 1, leap) {
+        m += 1;
+    }
+    m as f64
+}
+
+fn DateFromTime(t: f64) -> f64 {
+    DayWithinYear(t) - MonthStart(MonthFromTime(t) as usize, InLeapYear(t)) + 1.
+}
+
+fn WeekDay(t: f64) -> f64 {
+    modulo(Day(t) + 4., 7.)
+}
+
+fn HourFromTime(t: f64) -> f64 {
+    modulo((t / MS_PER_HOUR).floor(), 24.)
+}
+
+fn MinFromTime(t: f64) -> f64 {
+    modulo((t / MS_PER_MINUTE).floor(), 60.)
+}
+
+fn SecFromTime(t: f64) -> f64 {
+    modulo((t / MS_PER_SECOND).floor(), 60.)
+}
+
+fn msFromTime(t: f64) -> f64 {
+    modulo(t, MS_PER_SECOND)
+}
+
+fn LocalTime(t: f64, vm: &VM) -> f64 {
+    t + vm.local_offset(t)
+}
+
+fn UTC(t: f64, vm: &VM) -> f64 {
+    if !t.is_finite() {
+        return f64::NAN;
+    }
+    t - vm.local_offset(t - vm.local_offset(t))
+}
+
+fn MakeTime(hour: f64, min: f64, sec: f64, ms: f64) -> f64 {
+    if !(hour.is_finite() && min.is_finite() && sec.is_finite() && ms.is_finite()) {
+        return f64::NAN;
+    }
+    ToInteger(hour) * MS_PER_HOUR
+        + ToInteger(min) * MS_PER_MINUTE
+        + ToInteger(sec) * MS_PER_SECOND
+        + ToInteger(ms)
+}
+
+fn MakeDay(year: f64, month: f64, date: f64) -> f64 {
+    if !(year.is_finite() && month.is_finite() && date.is_finite()) {
+        return f64::NAN;
+    }
+    let (y, m, dt) = (ToInteger(year), ToInteger(month), ToInteger(date));
+    let ym = y + (m / 12.).floor();
+    let mn = modulo(m, 12.);
+    let t = TimeFromYear(ym);
+    if !t.is_finite() || ym.abs() > 400000. {
+        return f64::NAN;
+    }
+    let leap = DaysInYear(ym) - 365.;
+    Day(t) + MonthStart(mn as usize, leap) + dt - 1.
+}
+
+fn MakeDate(day: f64, time: f64) -> f64 {
+    if !(day.is_finite() && time.is_finite()) {
+        return f64::NAN;
+    }
+    day * MS_PER_DAY + time
+}
+
+fn TimeClip(time: f64) -> f64 {
+    if !time.is_finite() || time.abs() > 8.64e15 {
+        return f64::NAN;
+    }
+    ToInteger(time) + 0.
+}
+
+// Two digit years given to the constructor and Date.UTC mean 19xx
+fn full_year(year: f64) -> f64 {
+    if !year.is_nan() && (0. ..=99.).contains(&ToInteger(year)) {
+        1900. + ToInteger(year)
+    } else {
+        year
+    }
+}
+
+// [year, month, date, hours, minutes, seconds, ms]
+type Fields = [f64; 7];
+
+fn fields_of(t: f64) -> Fields {
+    [
+        YearFromTime(t),
+        MonthFromTime(t),
+        DateFromTime(t),
+        HourFromTime(t),
+        MinFromTime(t),
+        SecFromTime(t),
+        msFromTime(t),
+    ]
+}
+
+fn time_of(fields: &Fields) -> f64 {
+    let [year, month, date, hours, minutes, seconds, ms] = *fields;
+    MakeDate(
+        MakeDay(year, month, date),
+        MakeTime(hours, minutes, seconds, ms),
+    )
+}
+
+fn year_string(year: f64) -> std::string::String {
+    if year < 0. {
+        format!("-{:04}", -year)
+    } else {
+        format!("{:04}", year)
+    }
+}
+
+fn time_zone_string(offset: f64) -> std::string::String {
+    let minutes = (offset / MS_PER_MINUTE).round();
+    let sign = if minutes < 0. { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!(
+        "GMT{}{:02}{:02}",
+        sign,
+        (minutes / 60.).floor(),
+        minutes % 60.
+    )
+}
+
+fn date_string(t: f64) -> std::string::String {
+    format!(
+        "{} {} {:02} {}",
+        WEEK_DAYS[WeekDay(t) as usize],
+        MONTHS[MonthFromTime(t) as usize],
+        DateFromTime(t),
+        year_string(YearFromTime(t))
+    )
+}
+
+fn time_string(t: f64) -> std::string::String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        HourFromTime(t),
+        MinFromTime(t),
+        SecFromTime(t)
+    )
+}
+
+pub fn to_string(t: f64, vm: &VM) -> std::string::String {
+    if t.is_nan() {
+        return "Invalid Date".to_string();
+    }
+    let offset = vm.local_offset(t);
+    let local = t + offset;
+    format!(
+        "{} {} {}",
+        date_string(local),
+        time_string(local),
+        time_zone_string(offset)
+    )
+}
+
+fn to_utc_string(t: f64) -> std::string::String {
+    if t.is_nan() {
+        return "Invalid Date".to_string();
+    }
+    format!(
+        "{}, {:02} {} {} {} GMT",
+        WEEK_DAYS[WeekDay(t) as usize],
+        DateFromTime(t),
+        MONTHS[MonthFromTime(t) as usize],
+        year_string(YearFromTime(t)),
+        time_string(t)
+    )
+}
+
+fn to_iso_string(t: f64) -> std::string::String {
+    let year = YearFromTime(t);
+    let year = if (0. ..=9999.).contains(&year) {
+        format!("{:04}", year)
+    } else if year < 0. {
+        format!("-{:06}", -year)
+    } else {
+        format!("+{:06}", year)
+    };
+    format!(
+        "{}-{:02}-{:02}T{}.{:03}Z",
+        year,
+        MonthFromTime(t) + 1.,
+        DateFromTime(t),
+        time_string(t),
+        msFromTime(t)
+    )
+}
+
+// Date.parse: accepts the ISO format and the output of toString and toUTCString
+fn parse(s: &str, vm: &VM) -> f64 {
+    match parse_iso(s.trim()).or_else(|| parse_string(s.trim())) {
+        Some((t, Some(offset))) => TimeClip(t - offset),
+        Some((t, None)) => TimeClip(UTC(t, vm)),
+        None => f64::NAN,
+    }
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(s: &'a str) -> Self {
+        Scanner {
+            chars: s.chars().peekable(),
+        }
+    }
+    fn eat(&mut self, c: char) -> bool {
+        if self.chars.peek() == Some(&c) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+    fn digits(&mut self, min: usize, max: usize) -> Option<f64> {
+        let mut n = 0.;
+        let mut count = 0;
+        while count < max {
+            match self.chars.peek().and_then(|c| c.to_digit(10)) {
+                Some(d) => {
+                    n = n * 10. + d as f64;
+                    count += 1;
+                    self.chars.next();
+                }
+                None => break,
+            }
+        }
+        if count >= min {
+            Some(n)
+        } else {
+            None
+        }
+    }
+    fn word(&mut self) -> std::string::String {
+        let mut word = std::string::String::new();
+        while let Some(c) = self.chars.peek() {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            word.push(*c);
+            self.chars.next();
+        }
+        word
+    }
+    fn skip_spaces(&mut self) {
+        while self.eat(' ') {}
+    }
+    fn at_end(&mut self) -> bool {
+        self.chars.peek().is_none()
+    }
+    // "Z", "+HH:mm", "+HHmm"
+    fn offset(&mut self) -> Option<Option<f64>> {
+        if self.eat('Z') {
+            return Some(Some(0.));
+        }
+        let sign = if self.eat('+') {
+            1.
+        } else if self.eat('-') {
+            -1.
+        } else {
+            return Some(None);
+        };
+        let hours = self.digits(2, 2)?;
+        self.eat(':');
+        let minutes = self.digits(2, 2)?;
+        Some(Some(sign * (hours * MS_PER_HOUR + minutes * MS_PER_MINUTE)))
+    }
+    // "HH:mm[:ss[.sss]]"
+    fn time(&mut self) -> Option<f64> {
+        let hours = self.digits(2, 2)?;
+        if !self.eat(':') {
+            return None;
+        }
+        let minutes = self.digits(2, 2)?;
+        let mut seconds = 0.;
+        let mut ms = 0.;
+        if self.eat(':') {
+            seconds = self.digits(2, 2)?;
+            if self.eat('.') {
+                let mut scale = 100.;
+                while let Some(d) = self.digits(1, 1) {
+                    ms += d * scale;
+                    scale /= 10.;
+                }
+                ms = ms.floor();
+            }
+        }
+        if hours > 24. || minutes > 59. || seconds > 59. {
+            return None;
+        }
+        if hours == 24. && (minutes != 0. || seconds != 0. || ms != 0.) {
+            return None;
+        }
+        Some(MakeTime(hours, minutes, seconds, ms))
+    }
+}
+
+// Returns the time value and the explicit UTC offset, if any
+fn parse_iso(s: &str) -> Option<(f64, Option<f64>)> {
+    let mut sc = Scanner::new(s);
+    let year = if sc.eat('+') {
+        sc.digits(6, 6)?
+    } else if sc.eat('-') {
+        -sc.digits(6, 6)?
+    } else {
+        sc.digits(4, 4)?
+    };
+    let mut month = 1.;
+    let mut date = 1.;
+    if sc.eat('-') {
+        month = sc.digits(2, 2)?;
+        if sc.eat('-') {
+            date = sc.digits(2, 2)?;
+        }
+    }
+    if !(1. ..=12.).contains(&month) || !(1. ..=31.).contains(&date) {
+        return None;
+    }
+    let day = MakeDay(year, month - 1., date);
+    if sc.at_end() {
+        // Date-only forms are UTC
+        return Some((MakeDate(day, 0.), Some(0.)));
+    }
+    if !sc.eat('T') {
+        return None;
+    }
+    let time = sc.time()?;
+    let offset = sc.offset()?;
+    if !sc.at_end() {
+        return None;
+    }
+    Some((MakeDate(day, time), offset))
+}
+
+// "Tue Mar 01 2016 10:00:00 GMT+0100" and "Tue, 01 Mar 2016 10:00:00 GMT"
+fn parse_string(s: &str) -> Option<(f64, Option<f64>)> {
+    let month_of = |word: &str| MONTHS.iter().position(|m| *m == word);
+    let mut sc = Scanner::new(s);
+    let mut word = sc.word();
+    if WEEK_DAYS.contains(&word.as_str()) {
+        sc.eat(',');
+        sc.skip_spaces();
+        word = sc.word();
+    }
+    let (month, date) = match month_of(&word) {
+        Some(month) => {
+            sc.skip_spaces();
+            (month, sc.digits(1, 2)?)
+        }
+        None if word.is_empty() => {
+            let date = sc.digits(1, 2)?;
+            sc.skip_spaces();
+            (month_of(&sc.word())?, date)
+        }
+        None => return None,
+    };
+    sc.skip_spaces();
+    let sign = if sc.eat('-') { -1. } else { 1. };
+    let year = sign * sc.digits(1, 6)?;
+    let day = MakeDay(year, month as f64, date);
+    sc.skip_spaces();
+    if sc.at_end() {
+        return Some((MakeDate(day, 0.), None));
+    }
+    let time = sc.time()?;
+    sc.skip_spaces();
+    let mut offset = None;
+    match sc.word().as_str() {
+        "" => (),
+        "GMT" | "UTC" | "Z" => {
+            offset = Some(sc.offset()?.unwrap_or(0.));
+        }
+        _ => return None,
+    }
+    sc.skip_spaces();
+    // Trailing zone name, e.g. "(Coordinated Universal Time)"
+    if !sc.at_end() && !sc.eat('(') {
+        return None;
+    }
+    Some((MakeDate(day, time), offset))
+}
+
+fn arg_number(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    match args.get(idx).unwrap_or(&Value::Undefined).ToNumber(vm)? {
+        Value::Number(n) => Ok(n),
+        _ => Err("Fatal Error: ToNumber didn't return Value::Number".into()),
+    }
+}
+
+// Time value from the (year, month [, date, hours, minutes, seconds, ms]) arguments
+fn time_from_args(vm: &mut VM, args: &[Value]) -> Result<f64, Value> {
+    let mut fields: Fields = [f64::NAN, f64::NAN, 1., 0., 0., 0., 0.];
+    for (idx, field) in fields.iter_mut().enumerate() {
+        if idx < args.len() {
+            *field = arg_number(vm, args, idx)?;
+        }
+    }
+    fields[0] = full_year(fields[0]);
+    Ok(time_of(&fields))
+}
+
+fn this_time(vm: &VM, this: &Value) -> Result<f64, Value> {
+    if let Value::Object(this) = this {
+        if let ObjectPayload::Date(ref d) = this.borrow().payload {
+            return Ok(d.value);
+        }
+    }
+    Err(vm
+        .ctx
+        .new_Error(ErrorKind::TypeError, "`this` is not a Date object"))
+}
+
+fn set_this_time(vm: &VM, this: &Value, t: f64) -> JSResult {
+    let t = TimeClip(t);
+    if let Value::Object(this) = this {
+        if let ObjectPayload::Date(ref mut d) = this.borrow_mut().payload {
+            d.value = t;
+            return Ok(t.into());
+        }
+    }
+    Err(vm
+        .ctx
+        .new_Error(ErrorKind::TypeError, "`this` is not a Date object"))
+}
+
+fn get_local(vm: &VM, this: &Value, field: fn(f64) -> f64) -> JSResult {
+    let t = this_time(vm, this)?;
+    if t.is_nan() {
+        return Ok(f64::NAN.into());
+    }
+    Ok(field(LocalTime(t, vm)).into())
+}
+
+fn get_utc(vm: &VM, this: &Value, field: fn(f64) -> f64) -> JSResult {
+    let t = this_time(vm, this)?;
+    if t.is_nan() {
+        return Ok(f64::NAN.into());
+    }
+    Ok(field(t).into())
+}
+
+const YEAR: usize = 0;
+const MONTH: usize = 1;
+const DATE: usize = 2;
+const HOURS: usize = 3;
+const MINUTES: usize = 4;
+const SECONDS: usize = 5;
+const MS: usize = 6;
+
+// Shared by the setters: replaces the fields from `first` onwards (up to the end of
+// the date or the time part) with the arguments, in local time or UTC.
+fn set_fields(vm: &mut VM, this: &Value, args: &[Value], first: usize, local: bool) -> JSResult {
+    let t = this_time(vm, this)?;
+    let t = if local { LocalTime(t, vm) } else { t };
+    let t = if t.is_nan() && first == YEAR { 0. } else { t };
+    let mut fields = fields_of(t);
+    let last = if first <= DATE { DATE } else { MS };
+    for (i, field) in fields[first..=last].iter_mut().enumerate() {
+        if i == 0 || i < args.len() {
+            *field = arg_number(vm, args, i)?;
+        }
+    }
+    let t = time_of(&fields);
+    set_this_time(vm, this, if local { UTC(t, vm) } else { t })
+}
+
+// JS Primitives
+pub fn function(vm: &mut VM, _this: &Value, _args: &[Value]) -> JSResult {
+    Ok(to_string(vm.now(), vm).into())
+}
+
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let t = match args.len() {
+        0 => vm.now(),
+        1 => match args[0].ToPrimitive(vm)? {
+            Value::String(ref s) => parse(&s.to_string_lossy(), vm),
+            v => arg_number(vm, &[v], 0)?,
+        },
+        _ => {
+            let t = time_from_args(vm, args)?;
+            UTC(t, vm)
+        }
+    };
+    Ok(vm.ctx.new_Date(TimeClip(t)))
+}
+
+use crate::js_impl;
+use crate::vm::context::Context;
+
+pub fn get_function_props(ctx: &Context) -> JSDict {
+    fn parse(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let s = args.first().unwrap_or(&Value::Undefined).ToString(vm)?;
+        Ok(self::parse(&s.unwrap_string().to_string_lossy(), vm).into())
+    }
+    fn UTC(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(TimeClip(time_from_args(vm, args)?).into())
+    }
+    let mut props = JSDict::new();
+    props.insert(
+        "parse".into(),
+        Property::new(ctx.new_BuiltinFunction("parse", parse, 1)),
+    );
+    props.insert(
+        "UTC".into(),
+        Property::new(ctx.new_BuiltinFunction("UTC", UTC, 7)),
+    );
+    props
+}
+
+js_impl! {
+    #[prop(name=valueOf, length=0)]
+    fn valueOf(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(this_time(vm, this)?.into())
+    },
+    #[prop(name=getTime, length=0)]
+    fn getTime(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(this_time(vm, this)?.into())
+    },
+    #[prop(name=toString, length=0)]
+    fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(to_string(this_time(vm, this)?, vm).into())
+    },
+    #[prop(name=toLocaleString, length=0)]
+    fn toLocaleString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(to_string(this_time(vm, this)?, vm).into())
+    },
+    #[prop(name=toDateString, length=0)]
+    fn toDateString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let t = this_time(vm, this)?;
+        if t.is_nan() {
+            return Ok("Invalid Date".into());
+        }
+        Ok(date_string(LocalTime(t, vm)).into())
+    },
+    #[prop(name=toTimeString, length=0)]
+    fn toTimeString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let t = this_time(vm, this)?;
+        if t.is_nan() {
+            return Ok("Invalid Date".into());
+        }
+        let offset = vm.local_offset(t);
+        Ok(format!("{} {}", time_string(t + offset), time_zone_string(offset)).into())
+    },
+    #[prop(name=toUTCString, length=0)]
+    fn toUTCString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(to_utc_string(this_time(vm, this)?).into())
+    },
+    #[prop(name=toGMTString, length=0)]
+    fn toGMTString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(to_utc_string(this_time(vm, this)?).into())
+    },
+    #[prop(name=toISOString, length=0)]
+    fn toISOString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let t = this_time(vm, this)?;
+        if t.is_nan() {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "Invalid time value"));
+        }
+        Ok(to_iso_string(t).into())
+    },
+    #[prop(name=getYear, length=0)]
+    fn getYear(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, |t| YearFromTime(t) - 1900.)
+    },
+    #[prop(name=getFullYear, length=0)]
+    fn getFullYear(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, YearFromTime)
+    },
+    #[prop(name=getUTCFullYear, length=0)]
+    fn getUTCFullYear(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, YearFromTime)
+    },
+    #[prop(name=getMonth, length=0)]
+    fn getMonth(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, MonthFromTime)
+    },
+    #[prop(name=getUTCMonth, length=0)]
+    fn getUTCMonth(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, MonthFromTime)
+    },
+    #[prop(name=getDate, length=0)]
+    fn getDate(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, DateFromTime)
+    },
+    #[prop(name=getUTCDate, length=0)]
+    fn getUTCDate(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, DateFromTime)
+    },
+    #[prop(name=getDay, length=0)]
+    fn getDay(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, WeekDay)
+    },
+    #[prop(name=getUTCDay, length=0)]
+    fn getUTCDay(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, WeekDay)
+    },
+    #[prop(name=getHours, length=0)]
+    fn getHours(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, HourFromTime)
+    },
+    #[prop(name=getUTCHours, length=0)]
+    fn getUTCHours(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, HourFromTime)
+    },
+    #[prop(name=getMinutes, length=0)]
+    fn getMinutes(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, MinFromTime)
+    },
+    #[prop(name=getUTCMinutes, length=0)]
+    fn getUTCMinutes(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, MinFromTime)
+    },
+    #[prop(name=getSeconds, length=0)]
+    fn getSeconds(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, SecFromTime)
+    },
+    #[prop(name=getUTCSeconds, length=0)]
+    fn getUTCSeconds(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, SecFromTime)
+    },
+    #[prop(name=getMilliseconds, length=0)]
+    fn getMilliseconds(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_local(vm, this, msFromTime)
+    },
+    #[prop(name=getUTCMilliseconds, length=0)]
+    fn getUTCMilliseconds(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        get_utc(vm, this, msFromTime)
+    },
+    #[prop(name=getTimezoneOffset, length=0)]
+    fn getTimezoneOffset(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let t = this_time(vm, this)?;
+        Ok(((t - LocalTime(t, vm)) / MS_PER_MINUTE).into())
+    },
+    #[prop(name=setTime, length=1)]
+    fn setTime(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        this_time(vm, this)?;
+        let t = arg_number(vm, args, 0)?;
+        set_this_time(vm, this, t)
+    },
+    #[prop(name=setMilliseconds, length=1)]
+    fn setMilliseconds(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MS, true)
+    },
+    #[prop(name=setUTCMilliseconds, length=1)]
+    fn setUTCMilliseconds(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MS, false)
+    },
+    #[prop(name=setSeconds, length=2)]
+    fn setSeconds(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, SECONDS, true)
+    },
+    #[prop(name=setUTCSeconds, length=2)]
+    fn setUTCSeconds(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, SECONDS, false)
+    },
+    #[prop(name=setMinutes, length=3)]
+    fn setMinutes(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MINUTES, true)
+    },
+    #[prop(name=setUTCMinutes, length=3)]
+    fn setUTCMinutes(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MINUTES, false)
+    },
+    #[prop(name=setHours, length=4)]
+    fn setHours(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, HOURS, true)
+    },
+    #[prop(name=setUTCHours, length=4)]
+    fn setUTCHours(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, HOURS, false)
+    },
+    #[prop(name=setDate, length=1)]
+    fn setDate(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, DATE, true)
+    },
+    #[prop(name=setUTCDate, length=1)]
+    fn setUTCDate(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, DATE, false)
+    },
+    #[prop(name=setMonth, length=2)]
+    fn setMonth(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MONTH, true)
+    },
+    #[prop(name=setUTCMonth, length=2)]
+    fn setUTCMonth(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, MONTH, false)
+    },
+    #[prop(name=setFullYear, length=3)]
+    fn setFullYear(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, YEAR, true)
+    },
+    #[prop(name=setUTCFullYear, length=3)]
+    fn setUTCFullYear(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        set_fields(vm, this, args, YEAR, false)
+    },
+    #[prop(name=setYear, length=1)]
+    fn setYear(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let year = arg_number(vm, args, 0)?;
+        if year.is_nan() {
+            this_time(vm, this)?;
+            return set_this_time(vm, this, f64::NAN);
+        }
+        set_fields(vm, this, &[full_year(year).into()], YEAR, true)
+    }
+}
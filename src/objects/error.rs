@@ -0,0 +1,130 @@
+use super::*;
+use crate::vm::context::Context;
+
+/// The native error types of ES5 15.11.6, plus `Error` itself
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ErrorKind {
+    Error,
+    EvalError,
+    RangeError,
+    ReferenceError,
+    SyntaxError,
+    TypeError,
+    URIError,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 7] = [
+        ErrorKind::Error,
+        ErrorKind::EvalError,
+        ErrorKind::RangeError,
+        ErrorKind::ReferenceError,
+        ErrorKind::SyntaxError,
+        ErrorKind::TypeError,
+        ErrorKind::URIError,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Error => "Error",
+            ErrorKind::EvalError => "EvalError",
+            ErrorKind::RangeError => "RangeError",
+            ErrorKind::ReferenceError => "ReferenceError",
+            ErrorKind::SyntaxError => "SyntaxError",
+            ErrorKind::TypeError => "TypeError",
+            ErrorKind::URIError => "URIError",
+        }
+    }
+    pub fn function(self) -> RJSFunc {
+        match self {
+            ErrorKind::Error => error,
+            ErrorKind::EvalError => eval_error,
+            ErrorKind::RangeError => range_error,
+            ErrorKind::ReferenceError => reference_error,
+            ErrorKind::SyntaxError => syntax_error,
+            ErrorKind::TypeError => type_error,
+            ErrorKind::URIError => uri_error,
+        }
+    }
+}
+
+/// Payload of objects whose [[Class]] is "Error"
+#[derive(Trace, Finalize, Debug)]
+pub struct Error;
+
+impl Error {
+    pub fn toString(&self, gcobj: &GcObject, vm: &mut VM) -> JSResult {
+        let name = match gcobj.Get(&"name".into(), vm)? {
+            Value::Undefined => "Error".into(),
+            v => v.to_string(),
+        };
+        let message = match gcobj.Get(&"message".into(), vm)? {
+            Value::Undefined => std::string::String::new(),
+            v => v.to_string(),
+        };
+        Ok(match (name.is_empty(), message.is_empty()) {
+            (_, true) => name.into(),
+            (true, false) => message.into(),
+            (false, false) => format!("{}: {}", name, message).into(),
+        })
+    }
+}
+
+// Calling an error constructor as a function is the same as `new`
+fn construct(vm: &mut VM, args: &[Value], kind: ErrorKind) -> JSResult {
+    let error = vm.ctx.new_Error(kind, "");
+    match args.first() {
+        None | Some(Value::Undefined) => (),
+        Some(message) => {
+            let message = message.ToString(vm)?;
+            error
+                .unwrap_object()
+                .borrow_mut()
+                .props
+                .insert("message".into(), Property::new(message).dont_enum());
+        }
+    }
+    Ok(error)
+}
+
+macro_rules! native_error {
+    ($($func:ident => $kind:ident),*) => {
+        $(
+            fn $func(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+                construct(vm, args, ErrorKind::$kind)
+            }
+        )*
+    };
+}
+
+native_error! {
+    error => Error,
+    eval_error => EvalError,
+    range_error => RangeError,
+    reference_error => ReferenceError,
+    syntax_error => SyntaxError,
+    type_error => TypeError,
+    uri_error => URIError
+}
+
+pub fn get_prototype_props(ctx: &Context, kind: ErrorKind) -> JSDict {
+    let mut props = JSDict::new();
+    props.insert("name".into(), Property::new(kind.name().into()).dont_enum());
+    props.insert("message".into(), Property::new("".into()).dont_enum());
+    if kind == ErrorKind::Error {
+        props.insert(
+            "toString".into(),
+            Property::new(ctx.new_BuiltinFunction("toString", toString, 0)).dont_enum(),
+        );
+    }
+    props
+}
+
+fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+    match this {
+        Value::Object(ref o) => Error.toString(o, vm),
+        _ => Err(vm.ctx.new_Error(
+            ErrorKind::TypeError,
+            "Error.prototype.toString called on non-object",
+        )),
+    }
+}
@@ -1,8 +1,13 @@
+use super::error::ErrorKind;
 use super::*;
+use crate::codegen::codegen::{check_function, gen_function};
 use crate::vm::code::Code;
+use crate::vm::jsstring::JSString;
+use crate::vm::scope::GcEnv;
+use gc::custom_trace;
 use std::rc::Rc;
 
-#[derive(Trace, Finalize)]
+#[derive(Trace, Finalize, Clone)]
 struct ConstructorMetaData {
     #[unsafe_ignore_trace]
     constructor: RJSFunc,
@@ -15,27 +20,62 @@ impl std::fmt::Debug for ConstructorMetaData {
     }
 }
 
+/// Payload of the arguments object of a function call
 #[derive(Trace, Finalize, Debug)]
+pub struct Arguments;
+
+#[derive(Trace, Finalize, Clone, Debug)]
 pub struct Function {
     name: String,
     length: usize,
     payload: FunctionPayload,
 }
 
-#[derive(Trace, Finalize)]
+#[derive(Trace, Finalize, Clone)]
 enum FunctionPayload {
     UserDefined(UserFunctionData),
     Primitive(PrimitiveFunctionData),
+    Bound(BoundFunctionData),
 }
 
-#[derive(Trace, Finalize, Debug)]
+#[derive(Clone, Debug)]
 struct UserFunctionData {
-    #[unsafe_ignore_trace]
     code: Rc<Code>,
     prototype: GcObject,
+    source: JSString,
+    // The scope chain the function was created in
+    env: Option<GcEnv>,
+    // The function a closure was made from, which owns the code's constants
+    template: Option<GcObject>,
+    // The object a class method is defined on, whose prototype `super` refers to
+    home: Option<GcObject>,
+}
+
+impl Finalize for UserFunctionData {}
+// Besides this function and its closures, the code is only shared with the
+// frames executing it, so its constants (nested function objects) are traced
+// as if owned here. Closures trace this function instead.
+unsafe impl Trace for UserFunctionData {
+    custom_trace!(this, {
+        match this.template {
+            Some(ref template) => mark(template),
+            None => mark(&*this.code),
+        }
+        mark(&this.prototype);
+        mark(&this.env);
+        mark(&this.home);
+    });
+}
+
+// Result of Function.prototype.bind
+#[derive(Trace, Finalize, Clone, Debug)]
+struct BoundFunctionData {
+    target: GcObject,
+    this: Value,
+    args: Vec<Value>,
 }
 
-#[derive(Trace, Finalize)]
+#[derive(Trace, Finalize, Clone)]
 struct PrimitiveFunctionData {
     #[unsafe_ignore_trace]
     func: RJSFunc,
@@ -48,41 +88,91 @@ impl std::fmt::Debug for FunctionPayload {
     }
 }
 
-#[derive(Trace, Finalize)]
-pub struct PrimitiveFunction {
-    pub name: &'static str,
-    #[unsafe_ignore_trace]
-    pub func: RJSFunc,
-    #[unsafe_ignore_trace]
-    pub constructor: Option<RJSFunc>,
-    pub length: usize,
-    pub prototype: GcObject,
-}
-
 impl Function {
     pub fn new_userdefined(
         code: Rc<Code>,
         name: String,
         length: usize,
         prototype: GcObject,
+        source: JSString,
     ) -> Self {
         Self {
             name,
             length,
-            payload: FunctionPayload::UserDefined(UserFunctionData { code, prototype }),
+            payload: FunctionPayload::UserDefined(UserFunctionData {
+                code,
+                prototype,
+                source,
+                env: None,
+                template: None,
+                home: None,
+            }),
+        }
+    }
+    // A copy of this user-defined function, the one of `template`, closing
+    // over `env`
+    pub fn closure(&self, template: &GcObject, prototype: GcObject, env: Option<GcEnv>) -> Self {
+        let mut function = self.clone();
+        if let FunctionPayload::UserDefined(ref mut data) = function.payload {
+            data.prototype = prototype;
+            data.env = env;
+            data.template = Some(template.clone());
+        }
+        function
+    }
+    pub fn home(&self) -> Option<GcObject> {
+        match self.payload {
+            FunctionPayload::UserDefined(UserFunctionData { ref home, .. }) => home.clone(),
+            _ => None,
+        }
+    }
+    pub fn set_home(&mut self, object: GcObject) {
+        if let FunctionPayload::UserDefined(ref mut data) = self.payload {
+            data.home = Some(object);
         }
     }
 
-    pub fn Call(&self, vm: &mut VM, args: &[Value]) -> JSResult {
+    pub fn Call(&self, gcobj: &GcObject, vm: &mut VM, this: Value, args: &[Value]) -> JSResult {
         match &self.payload {
-            FunctionPayload::Primitive(PrimitiveFunctionData { func, .. }) => func(vm, args),
-            FunctionPayload::UserDefined(UserFunctionData { code, .. }) => {
-                vm.call_code(code.clone(), self.length, args)
+            FunctionPayload::Primitive(PrimitiveFunctionData { func, .. }) => func(vm, &this, args),
+            FunctionPayload::UserDefined(UserFunctionData { code, .. })
+                if code.class_constructor =>
+            {
+                let message = format!(
+                    "Class constructor {} cannot be invoked without 'new'",
+                    self.name
+                );
+                Err(vm.ctx.new_Error(ErrorKind::TypeError, &message))
+            }
+            FunctionPayload::UserDefined(UserFunctionData { code, env, .. }) => {
+                // Non-strict code sees the global object in place of a null or
+                // undefined this, and primitives as objects (ES5 10.4.3)
+                let this = match this {
+                    this if code.strict => this,
+                    Value::Undefined | Value::Null => vm.global().clone().into(),
+                    this => this.as_object(vm.ctx).into(),
+                };
+                vm.call_code(code.clone(), env.clone(), args, gcobj, Some(this), None)
+            }
+            FunctionPayload::Bound(BoundFunctionData {
+                target,
+                this,
+                args: bound_args,
+            }) => {
+                let args = [&bound_args[..], args].concat();
+                target.Call(vm, this.clone(), &args)
             }
         }
     }
-    // TODO:
-    pub fn Construct(&self, vm: &mut VM, args: &[Value]) -> JSResult {
+    // [[Construct]], where `new_target` is the constructor `new` was applied
+    // to, which the prototype of the new object comes from (ES6 9.2.2)
+    pub fn Construct(
+        &self,
+        gcobj: &GcObject,
+        vm: &mut VM,
+        args: &[Value],
+        new_target: &GcObject,
+    ) -> JSResult {
         match &self.payload {
             FunctionPayload::Primitive(PrimitiveFunctionData {
                 constructor_metadata,
@@ -90,20 +180,96 @@ impl Function {
             }) => {
                 if let Some(constructor_metadata) = constructor_metadata {
                     let cons = constructor_metadata.constructor;
-                    cons(vm, args)
+                    let object = cons(vm, &Value::Undefined, args)?;
+                    // Reached through super() from a class extending a builtin
+                    if !Gc::ptr_eq(gcobj, new_target) {
+                        let prototype = new_target.Get(&"prototype".into(), vm)?;
+                        if let (Value::Object(o), Value::Object(prototype)) = (&object, &prototype)
+                        {
+                            o.borrow_mut().__proto__ = Some(prototype.clone());
+                        }
+                    }
+                    Ok(object)
                 } else {
-                    Err("Object is not constructible".into())
+                    let message = format!("{} is not a constructor", self.name);
+                    Err(vm.ctx.new_Error(ErrorKind::TypeError, &message))
                 }
             }
-            FunctionPayload::UserDefined(UserFunctionData { prototype, .. }) => {
-                let this = vm.ctx.new_Object(Some(prototype.clone()));
-                vm.push_this(this.clone());
-                this.unwrap_object().borrow_mut().__proto__ = prototype.clone().into();
-                self.Call(vm, args)?;
-                Ok(vm.pop_this())
+            FunctionPayload::UserDefined(UserFunctionData { code, .. }) if code.arrow => Err(vm
+                .ctx
+                .new_Error(ErrorKind::TypeError, "Arrow function is not a constructor")),
+            FunctionPayload::UserDefined(UserFunctionData { code, .. }) if code.method => {
+                let message = format!("{} is not a constructor", self.name);
+                Err(vm.ctx.new_Error(ErrorKind::TypeError, &message))
+            }
+            FunctionPayload::UserDefined(UserFunctionData {
+                code,
+                env,
+                prototype,
+                ..
+            }) => {
+                let prototype = if Gc::ptr_eq(gcobj, new_target) {
+                    prototype.clone()
+                } else {
+                    match new_target.Get(&"prototype".into(), vm)? {
+                        Value::Object(ref o) => o.clone(),
+                        _ => vm.ctx.Object_prototype.clone(),
+                    }
+                };
+                // A derived class constructor gets its this from super()
+                let this = match code.derived {
+                    true => None,
+                    false => Some(vm.ctx.new_Object(Some(prototype))),
+                };
+                let new_target = Some(new_target.clone());
+                vm.call_code(code.clone(), env.clone(), args, gcobj, this, new_target)
+            }
+            FunctionPayload::Bound(BoundFunctionData {
+                target,
+                args: bound_args,
+                ..
+            }) => {
+                let args = [&bound_args[..], args].concat();
+                target.Construct(vm, &args)
             }
         }
     }
+    // [[HasInstance]] (ES5 15.3.5.3, and 15.3.4.5.3 for bound functions)
+    pub fn HasInstance(&self, gcobj: &GcObject, vm: &mut VM, value: &Value) -> JSResult {
+        if let FunctionPayload::Bound(BoundFunctionData { target, .. }) = &self.payload {
+            return target.HasInstance(value, vm);
+        }
+        let mut proto = match value {
+            Value::Object(o) => o.borrow().__proto__.clone(),
+            _ => return Ok(false.into()),
+        };
+        let prototype = match gcobj.Get(&"prototype".into(), vm)? {
+            Value::Object(ref o) => o.clone(),
+            ref other => {
+                let message = format!(
+                    "Function has non-object prototype '{}' in instanceof check",
+                    other.ToString(vm)?
+                );
+                return Err(vm.ctx.new_Error(ErrorKind::TypeError, &message));
+            }
+        };
+        while let Some(o) = proto {
+            if Gc::ptr_eq(&o, &prototype) {
+                return Ok(true.into());
+            }
+            proto = o.borrow().__proto__.clone();
+        }
+        Ok(false.into())
+    }
+    pub fn toString(&self) -> Value {
+        match &self.payload {
+            FunctionPayload::UserDefined(UserFunctionData { source, .. }) => source.clone().into(),
+            FunctionPayload::Primitive(_) => {
+                format!("function {}() {{ [native code] }}", self.name).into()
+            }
+            FunctionPayload::Bound(_) => "function () { [native code] }".into(),
+        }
+    }
 }
 
 impl Function {
@@ -126,6 +292,19 @@ impl Function {
             length,
         }
     }
+    pub fn new_bound(
+        name: String,
+        length: usize,
+        target: GcObject,
+        this: Value,
+        args: Vec<Value>,
+    ) -> Self {
+        Self {
+            name,
+            payload: FunctionPayload::Bound(BoundFunctionData { target, this, args }),
+            length,
+        }
+    }
     pub fn new_builtin(name: String, func: RJSFunc, length: usize) -> Self {
         Self {
             name,
@@ -139,11 +318,107 @@ impl Function {
 }
 
 // JS Primitives
-// function Function ()
-pub fn function(_vm: &mut VM, _args: &[Value]) -> JSResult {
-    Err("Not impl".into())
+// function Function (p1, p2, ... , pn, body)
+pub fn function(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    constructor(vm, this, args)
 }
 
-pub fn constructor(_vm: &mut VM, _args: &[Value]) -> JSResult {
-    Err("Not impl".into())
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let mut texts = Vec::with_capacity(args.len());
+    for arg in args {
+        texts.push(arg.ToString(vm)?.unwrap_string().to_string_lossy());
+    }
+    let body = texts.pop().unwrap_or_default();
+    let params = texts.join(",");
+    // Each part must parse alone, so a comment or brace can't span the two
+    check_function(&format!("function anonymous({}\n) {{\n}}", params))
+        .and_then(|_| check_function(&format!("function anonymous(\n) {{\n{}\n}}", body)))
+        .map_err(|message| vm.ctx.new_Error(ErrorKind::SyntaxError, &message))?;
+    let source = format!("function anonymous({}\n) {{\n{}\n}}", params, body);
+    gen_function(&source, vm.ctx)
+        .map_err(|message| vm.ctx.new_Error(ErrorKind::SyntaxError, &message))
+}
+
+fn this_function(vm: &VM, this: &Value) -> Result<GcObject, Value> {
+    if let Value::Object(o) = this {
+        if let ObjectPayload::Function(_) = o.borrow().payload {
+            return Ok(o.clone());
+        }
+    }
+    Err(vm.ctx.new_Error(
+        ErrorKind::TypeError,
+        "Function.prototype method called on incompatible receiver",
+    ))
+}
+
+// CreateListFromArrayLike
+fn list_from_array_like(vm: &mut VM, value: &Value) -> Result<Vec<Value>, Value> {
+    let object = match value {
+        Value::Undefined | Value::Null => return Ok(Vec::new()),
+        Value::Object(o) => o,
+        _ => {
+            return Err(vm.ctx.new_Error(
+                ErrorKind::TypeError,
+                "CreateListFromArrayLike called on non-object",
+            ))
+        }
+    };
+    let length = object.Get(&"length".into(), vm)?;
+    let length = match length.ToNumber(vm)? {
+        Value::Number(n) if n > MAX_ARGUMENTS => {
+            return Err(vm
+                .ctx
+                .new_Error(ErrorKind::RangeError, "Too many arguments in function call"))
+        }
+        Value::Number(n) if n > 0. => n as usize,
+        _ => 0,
+    };
+    (0..length)
+        .map(|k| object.Get(&k.to_string().into(), vm))
+        .collect()
+}
+
+const MAX_ARGUMENTS: f64 = 65535.;
+
+use crate::js_impl;
+use crate::vm::context::Context;
+
+js_impl! {
+    #[prop(name=call, length=1)]
+    fn call(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let func = this_function(vm, this)?;
+        let this = args.first().cloned().unwrap_or_default();
+        func.Call(vm, this, args.get(1..).unwrap_or(&[]))
+    },
+    #[prop(name=apply, length=2)]
+    fn apply(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let func = this_function(vm, this)?;
+        let this = args.first().cloned().unwrap_or_default();
+        let args = list_from_array_like(vm, &args.get(1).cloned().unwrap_or_default())?;
+        func.Call(vm, this, &args)
+    },
+    #[prop(name=bind, length=1)]
+    fn bind(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let target = this_function(vm, this)?;
+        let this = args.first().cloned().unwrap_or_default();
+        let args = args.get(1..).unwrap_or(&[]).to_vec();
+        let length = match target.Get(&"length".into(), vm)? {
+            Value::Number(n) => (n - args.len() as f64).max(0.) as usize,
+            _ => 0,
+        };
+        let name = match target.Get(&"name".into(), vm)? {
+            Value::String(ref s) => s.to_string_lossy(),
+            _ => std::string::String::new(),
+        };
+        Ok(vm.ctx.new_BoundFunction(target, this, args, format!("bound {}", name), length))
+    },
+    #[prop(name=toString, length=0)]
+    fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let func = this_function(vm, this)?;
+        let func = func.borrow();
+        match func.payload {
+            ObjectPayload::Function(ref f) => Ok(f.toString()),
+            _ => unreachable!(),
+        }
+    }
 }
@@ -0,0 +1,250 @@
+use super::error::ErrorKind;
+use super::*;
+use crate::vm::context::Context;
+use crate::vm::jsstring::JSString;
+
+fn arg_units(vm: &mut VM, args: &[Value], idx: usize) -> Result<Vec<u16>, Value> {
+    let arg = args.get(idx).cloned().unwrap_or_default();
+    Ok(arg.ToString(vm)?.unwrap_string().units().to_vec())
+}
+
+fn arg_number(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    match args.get(idx).cloned().unwrap_or_default().ToNumber(vm)? {
+        Value::Number(n) => Ok(n),
+        _ => Err("Fatal Error: ToNumber didn't return Value::Number".into()),
+    }
+}
+
+fn skip_whitespace(units: &[u16]) -> &[u16] {
+    let start = units
+        .iter()
+        .position(|c| !string::is_whitespace(*c))
+        .unwrap_or(units.len());
+    &units[start..]
+}
+
+fn constant(value: Value) -> Property {
+    Property::new(value).read_only().dont_enum().dont_delete()
+}
+
+fn parseInt(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    let radix = arg_number(vm, args, 1)?;
+    // ToInt32
+    let mut radix = if radix.is_finite() {
+        radix.trunc().rem_euclid(4294967296.) as u32 as i32
+    } else {
+        0
+    };
+    let s = std::string::String::from_utf16_lossy(skip_whitespace(&units));
+    let (sign, mut s) = number::split_sign(&s);
+    let mut strip_prefix = true;
+    if radix != 0 {
+        if !(2..=36).contains(&radix) {
+            return Ok(f64::NAN.into());
+        }
+        strip_prefix = radix == 16;
+    } else {
+        radix = 10;
+    }
+    if strip_prefix {
+        if let Some(rest) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            s = rest;
+            radix = 16;
+        }
+    }
+    let len = number::radix_prefix(s, radix as u32);
+    if len == 0 {
+        return Ok(f64::NAN.into());
+    }
+    Ok((sign * number::radix_value(&s[..len], radix as u32)).into())
+}
+
+fn parseFloat(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    let s = std::string::String::from_utf16_lossy(skip_whitespace(&units));
+    let (sign, s) = number::split_sign(&s);
+    if s.starts_with("Infinity") {
+        return Ok((sign * f64::INFINITY).into());
+    }
+    Ok(match number::decimal_prefix(s) {
+        0 => f64::NAN,
+        len => sign * s[..len].parse::<f64>().unwrap(),
+    }
+    .into())
+}
+
+fn isNaN(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    Ok(arg_number(vm, args, 0)?.is_nan().into())
+}
+
+fn isFinite(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    Ok(arg_number(vm, args, 0)?.is_finite().into())
+}
+
+fn push_hex(res: &mut Vec<u16>, prefix: &str, value: u32, width: usize) {
+    let escape = format!("{}{:0width$X}", prefix, value, width = width);
+    res.extend(escape.encode_utf16());
+}
+
+fn hex_value(units: &[u16]) -> Option<u32> {
+    units.iter().try_fold(0, |acc, c| {
+        let digit = char::from_u32(*c as u32)?.to_digit(16)?;
+        Some(acc * 16 + digit)
+    })
+}
+
+fn escape(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    let mut res = Vec::with_capacity(units.len());
+    for c in units {
+        match c {
+            0x30..=0x39 | 0x41..=0x5A | 0x61..=0x7A => res.push(c),
+            _ if c < 0x80 && b"@*_+-./".contains(&(c as u8)) => res.push(c),
+            0..=0xFF => push_hex(&mut res, "%", c as u32, 2),
+            _ => push_hex(&mut res, "%u", c as u32, 4),
+        }
+    }
+    Ok(JSString::from(res).into())
+}
+
+fn unescape(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    let mut res = Vec::with_capacity(units.len());
+    let mut k = 0;
+    while k < units.len() {
+        let (c, len) = match units[k] {
+            0x25 if units.get(k + 1) == Some(&0x75) => match units.get(k + 2..k + 6) {
+                Some(digits) => hex_value(digits).map_or((0x25, 1), |c| (c as u16, 6)),
+                None => (0x25, 1),
+            },
+            0x25 => match units.get(k + 1..k + 3) {
+                Some(digits) => hex_value(digits).map_or((0x25, 1), |c| (c as u16, 3)),
+                None => (0x25, 1),
+            },
+            c => (c, 1),
+        };
+        res.push(c);
+        k += len;
+    }
+    Ok(JSString::from(res).into())
+}
+
+const URI_RESERVED: &[u8] = b";/?:@&=+$,";
+const URI_MARKS: &[u8] = b"-_.!~*'()";
+
+fn uri_error(vm: &VM) -> Value {
+    vm.ctx.new_Error(ErrorKind::URIError, "URI malformed")
+}
+
+// Encode (ES5 15.1.3): percent-encodes the UTF-8 form of every character
+// that is neither alphanumeric nor in `unescaped`
+fn encode(vm: &VM, units: &[u16], unescaped: &[u8]) -> JSResult {
+    let mut res = Vec::with_capacity(units.len());
+    for c in char::decode_utf16(units.iter().cloned()) {
+        let c = c.map_err(|_| uri_error(vm))?;
+        if c.is_ascii_alphanumeric() || (c.is_ascii() && unescaped.contains(&(c as u8))) {
+            res.push(c as u16);
+            continue;
+        }
+        let mut buf = [0; 4];
+        for b in c.encode_utf8(&mut buf).bytes() {
+            push_hex(&mut res, "%", b as u32, 2);
+        }
+    }
+    Ok(JSString::from(res).into())
+}
+
+// Decode (ES5 15.1.3): escapes of characters in `reserved` are kept as is
+fn decode(vm: &VM, units: &[u16], reserved: &[u8]) -> JSResult {
+    let byte_at = |k: usize| match units.get(k..k + 3) {
+        Some([0x25, digits @ ..]) => hex_value(digits).map(|b| b as u8),
+        _ => None,
+    };
+    let mut res = Vec::with_capacity(units.len());
+    let mut k = 0;
+    while k < units.len() {
+        if units[k] != 0x25 {
+            res.push(units[k]);
+            k += 1;
+            continue;
+        }
+        let start = k;
+        let b = byte_at(k).ok_or_else(|| uri_error(vm))?;
+        k += 3;
+        if b < 0x80 {
+            if reserved.contains(&b) {
+                res.extend_from_slice(&units[start..k]);
+            } else {
+                res.push(b as u16);
+            }
+            continue;
+        }
+        let n = b.leading_ones() as usize;
+        if n == 1 || n > 4 {
+            return Err(uri_error(vm));
+        }
+        let mut bytes = vec![b];
+        for _ in 1..n {
+            match byte_at(k) {
+                Some(b) if b & 0xC0 == 0x80 => bytes.push(b),
+                _ => return Err(uri_error(vm)),
+            }
+            k += 3;
+        }
+        // Rejects overlong forms and encoded surrogates
+        let s = std::str::from_utf8(&bytes).map_err(|_| uri_error(vm))?;
+        let mut buf = [0; 2];
+        for c in s.chars() {
+            res.extend_from_slice(c.encode_utf16(&mut buf));
+        }
+    }
+    Ok(JSString::from(res).into())
+}
+
+fn encodeURI(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    encode(vm, &units, &[URI_RESERVED, URI_MARKS, b"#"].concat())
+}
+
+fn decodeURI(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    decode(vm, &units, &[URI_RESERVED, b"#"].concat())
+}
+
+fn encodeURIComponent(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    encode(vm, &units, URI_MARKS)
+}
+
+fn decodeURIComponent(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let units = arg_units(vm, args, 0)?;
+    decode(vm, &units, &[])
+}
+
+// Value and function properties of the global object (ES5 15.1.1, 15.1.2, 15.1.3)
+pub fn get_props(ctx: &Context) -> JSDict {
+    let mut props = JSDict::new();
+    props.insert("NaN".into(), constant(f64::NAN.into()));
+    props.insert("Infinity".into(), constant(f64::INFINITY.into()));
+    props.insert("undefined".into(), constant(Value::Undefined));
+    let functions: [(&'static str, RJSFunc, usize); 10] = [
+        ("parseInt", parseInt, 2),
+        ("parseFloat", parseFloat, 1),
+        ("isNaN", isNaN, 1),
+        ("isFinite", isFinite, 1),
+        ("escape", escape, 1),
+        ("unescape", unescape, 1),
+        ("encodeURI", encodeURI, 1),
+        ("decodeURI", decodeURI, 1),
+        ("encodeURIComponent", encodeURIComponent, 1),
+        ("decodeURIComponent", decodeURIComponent, 1),
+    ];
+    for (name, func, length) in functions.iter() {
+        props.insert(
+            (*name).into(),
+            Property::new(ctx.new_BuiltinFunction(name, *func, *length)).dont_enum(),
+        );
+    }
+    props
+}
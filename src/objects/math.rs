@@ -0,0 +1,162 @@
+use super::*;
+
+/// The payload of the Math object, whose [[Class]] is "Math" (ES5 15.8)
+#[derive(Trace, Finalize, Debug)]
+pub struct Math;
+
+fn arg_number(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    match args.get(idx).unwrap_or(&Value::Undefined).ToNumber(vm)? {
+        Value::Number(n) => Ok(n),
+        _ => Err("Fatal Error: ToNumber didn't return Value::Number".into()),
+    }
+}
+
+fn constant(value: f64) -> Property {
+    Property {
+        value: value.into(),
+        accessor: None,
+        read_only: true,
+        dont_enum: true,
+        dont_delete: true,
+        internal: false,
+    }
+}
+
+// Math.round rounds halves towards +Infinity and keeps the sign of zero
+fn round_half_up(x: f64) -> f64 {
+    if !x.is_finite() || x == 0. {
+        return x;
+    }
+    let floor = x.floor();
+    let res = if x - floor >= 0.5 { floor + 1. } else { floor };
+    if res == 0. && x < 0. {
+        -0.
+    } else {
+        res
+    }
+}
+
+fn js_pow(x: f64, y: f64) -> f64 {
+    if y.is_nan() {
+        f64::NAN
+    } else if y == 0. {
+        1.
+    } else if x.abs() == 1. && y.is_infinite() {
+        f64::NAN
+    } else {
+        x.powf(y)
+    }
+}
+
+pub fn get_props(ctx: &Context) -> JSDict {
+    let mut props = get_prototype_props(ctx);
+    props.insert("E".into(), constant(std::f64::consts::E));
+    props.insert("LN10".into(), constant(std::f64::consts::LN_10));
+    props.insert("LN2".into(), constant(std::f64::consts::LN_2));
+    props.insert("LOG2E".into(), constant(std::f64::consts::LOG2_E));
+    props.insert("LOG10E".into(), constant(std::f64::consts::LOG10_E));
+    props.insert("PI".into(), constant(std::f64::consts::PI));
+    props.insert("SQRT1_2".into(), constant(std::f64::consts::FRAC_1_SQRT_2));
+    props.insert("SQRT2".into(), constant(std::f64::consts::SQRT_2));
+    props
+}
+
+use crate::js_impl;
+use crate::vm::context::Context;
+
+js_impl! {
+    #[prop(name=abs, length=1)]
+    fn abs(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.abs().into())
+    },
+    #[prop(name=acos, length=1)]
+    fn acos(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.acos().into())
+    },
+    #[prop(name=asin, length=1)]
+    fn asin(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.asin().into())
+    },
+    #[prop(name=atan, length=1)]
+    fn atan(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.atan().into())
+    },
+    #[prop(name=atan2, length=2)]
+    fn atan2(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let y = arg_number(vm, args, 0)?;
+        let x = arg_number(vm, args, 1)?;
+        Ok(y.atan2(x).into())
+    },
+    #[prop(name=ceil, length=1)]
+    fn ceil(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.ceil().into())
+    },
+    #[prop(name=cos, length=1)]
+    fn cos(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.cos().into())
+    },
+    #[prop(name=exp, length=1)]
+    fn exp(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.exp().into())
+    },
+    #[prop(name=floor, length=1)]
+    fn floor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.floor().into())
+    },
+    #[prop(name=log, length=1)]
+    fn log(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.ln().into())
+    },
+    #[prop(name=max, length=2)]
+    fn max(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let mut res = f64::NEG_INFINITY;
+        for idx in 0..args.len() {
+            let n = arg_number(vm, args, idx)?;
+            if n.is_nan() || res.is_nan() {
+                res = f64::NAN;
+            } else if n > res || (n == 0. && res == 0. && res.is_sign_negative()) {
+                res = n;
+            }
+        }
+        Ok(res.into())
+    },
+    #[prop(name=min, length=2)]
+    fn min(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let mut res = f64::INFINITY;
+        for idx in 0..args.len() {
+            let n = arg_number(vm, args, idx)?;
+            if n.is_nan() || res.is_nan() {
+                res = f64::NAN;
+            } else if n < res || (n == 0. && res == 0. && n.is_sign_negative()) {
+                res = n;
+            }
+        }
+        Ok(res.into())
+    },
+    #[prop(name=pow, length=2)]
+    fn pow(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let x = arg_number(vm, args, 0)?;
+        let y = arg_number(vm, args, 1)?;
+        Ok(js_pow(x, y).into())
+    },
+    #[prop(name=random, length=0)]
+    fn random(vm: &mut VM, _this: &Value, _args: &[Value]) -> JSResult {
+        Ok(vm.random().into())
+    },
+    #[prop(name=round, length=1)]
+    fn round(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(round_half_up(arg_number(vm, args, 0)?).into())
+    },
+    #[prop(name=sin, length=1)]
+    fn sin(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.sin().into())
+    },
+    #[prop(name=sqrt, length=1)]
+    fn sqrt(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.sqrt().into())
+    },
+    #[prop(name=tan, length=1)]
+    fn tan(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(arg_number(vm, args, 0)?.tan().into())
+    }
+}
@@ -1,24 +1,34 @@
 #![allow(non_snake_case)]
 
+pub mod array;
+pub mod boolean;
+pub mod date;
+pub mod error;
 pub mod function;
+pub mod global;
+pub mod math;
 pub mod number;
 pub mod object;
 pub mod string;
 
+use crate::vm::jsstring::JSString;
 use crate::vm::value::Value;
 use crate::vm::vm::VM;
+use gc::custom_trace;
 pub use gc::{Finalize, Gc, GcCell, Trace};
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::ops::{Deref, DerefMut};
 
 type GcBox<T> = Gc<GcCell<T>>;
 pub type GcObject = GcBox<Object>;
-pub type JSDict = std::collections::HashMap<String, Property>;
 pub type JSResult = Result<Value, Value>;
-pub type RJSFunc = fn(&mut VM, &[Value]) -> JSResult;
+pub type RJSFunc = fn(&mut VM, &Value, &[Value]) -> JSResult;
 
-#[derive(Trace, Finalize, Debug)]
+#[derive(Trace, Finalize, Clone, Debug)]
 pub struct Property {
     value: Value,
+    // Set for accessor properties, whose `value` and `read_only` are unused
+    accessor: Option<Accessor>,
     read_only: bool,
     dont_enum: bool,
     dont_delete: bool,
@@ -29,23 +39,201 @@ impl Property {
     pub fn new(value: Value) -> Self {
         Property {
             value,
+            accessor: None,
             read_only: false,
             dont_enum: false,
             dont_delete: false,
             internal: false,
         }
     }
+    pub fn accessor(get: Value, set: Value) -> Self {
+        Property {
+            value: Value::Undefined,
+            accessor: Some(Accessor { get, set }),
+            read_only: false,
+            dont_enum: false,
+            dont_delete: false,
+            internal: false,
+        }
+    }
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+    pub fn dont_enum(mut self) -> Self {
+        self.dont_enum = true;
+        self
+    }
+    pub fn dont_delete(mut self) -> Self {
+        self.dont_delete = true;
+        self
+    }
+}
+
+/// The getter and setter functions of an accessor property, undefined when absent
+#[derive(Trace, Finalize, Clone, Debug)]
+pub struct Accessor {
+    get: Value,
+    set: Value,
+}
+
+/// Own properties of an object, kept in the order they were added
+#[derive(Clone, Debug, Default)]
+pub struct JSDict(IndexMap<JSString, Property>);
+
+impl JSDict {
+    pub fn new() -> Self {
+        JSDict(IndexMap::new())
+    }
+}
+
+impl Deref for JSDict {
+    type Target = IndexMap<JSString, Property>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for JSDict {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl IntoIterator for JSDict {
+    type Item = (JSString, Property);
+    type IntoIter = indexmap::map::IntoIter<JSString, Property>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Finalize for JSDict {}
+unsafe impl Trace for JSDict {
+    custom_trace!(this, {
+        for prop in this.0.values() {
+            mark(prop);
+        }
+    });
 }
 
 #[derive(Trace, Finalize, Debug)]
 pub struct Object {
     pub __proto__: Option<GcObject>,
     pub payload: ObjectPayload,
-    pub props: HashMap<String, Property>,
+    pub props: JSDict,
+    // [[Extensible]]: whether own properties may be added
+    pub extensible: bool,
+}
+
+impl Object {
+    /// [[Class]], as reported by `Object.prototype.toString`
+    pub fn Class(&self) -> &'static str {
+        match self.payload {
+            ObjectPayload::Arguments(_) => "Arguments",
+            ObjectPayload::Array(_) => "Array",
+            ObjectPayload::Boolean(_) => "Boolean",
+            ObjectPayload::Date(_) => "Date",
+            ObjectPayload::Error(_) => "Error",
+            ObjectPayload::Math(_) => "Math",
+            ObjectPayload::Number(_) => "Number",
+            ObjectPayload::String(_) => "String",
+            ObjectPayload::Function(_) => "Function",
+            ObjectPayload::Regular(_) => "Object",
+        }
+    }
+    // [[GetOwnProperty]] (ES5 8.12.1), which a String object extends with its
+    // index properties (ES5 15.5.5.2)
+    pub fn GetOwnProperty(&self, key: &JSString) -> Option<Property> {
+        match (self.props.get(key), &self.payload) {
+            (Some(prop), _) => Some(prop.clone()),
+            (None, ObjectPayload::String(s)) => s.index_property(key),
+            (None, _) => None,
+        }
+    }
+    // [[GetProperty]] (ES5 8.12.2): the own or inherited property, if any
+    pub fn GetProperty(&self, key: &JSString) -> Option<Property> {
+        match self.GetOwnProperty(key) {
+            Some(prop) => Some(prop),
+            None => match self.__proto__ {
+                Some(ref proto) => proto.borrow().GetProperty(key),
+                None => None,
+            },
+        }
+    }
+    // Stores a data property without any checks, for [[Put]] and for filling
+    // in fresh objects
+    pub fn PutValue(&mut self, key: JSString, value: Value) {
+        if let ObjectPayload::Array(_) = self.payload {
+            return array::Put(self, key, value);
+        }
+        match self.props.get_mut(&key) {
+            Some(prop) => prop.value = value,
+            None => {
+                self.props.insert(key, Property::new(value));
+            }
+        }
+    }
+    // Sets the getter or setter of an own accessor property, replacing any data
+    // property, as a `get`/`set` in an object literal does
+    pub fn PutAccessor(&mut self, key: JSString, get: Option<Value>, set: Option<Value>) {
+        if let Some(Property {
+            accessor: Some(ref mut accessor),
+            ..
+        }) = self.props.get_mut(&key)
+        {
+            if let Some(get) = get {
+                accessor.get = get;
+            }
+            if let Some(set) = set {
+                accessor.set = set;
+            }
+            return;
+        }
+        let prop = Property::accessor(get.unwrap_or_default(), set.unwrap_or_default());
+        self.props.insert(key, prop);
+    }
+    /// Makes every own property read-only, save accessors, and undeletable,
+    /// and the object non-extensible (ES5 15.2.3.9)
+    pub fn freeze(&mut self) {
+        for prop in self.props.values_mut() {
+            prop.read_only = prop.accessor.is_none();
+            prop.dont_delete = true;
+        }
+        self.extensible = false;
+    }
+    /// Own property names in enumeration order: array indices first in
+    /// ascending order, then the other names in the order they were added.
+    /// Those of a String object include the indices of its code units.
+    pub fn own_keys(&self) -> Vec<JSString> {
+        let mut keys: Vec<JSString> = match self.payload {
+            ObjectPayload::String(ref s) => s.index_keys().collect(),
+            _ => Vec::new(),
+        };
+        // Those stored once redefined are already listed
+        let len = keys.len();
+        let stored = self
+            .props
+            .keys()
+            .filter(|key| !matches!(array::array_index(key), Some(idx) if (idx as usize) < len));
+        keys.extend(stored.cloned());
+        // The sort is stable, so the other names keep their order
+        keys.sort_by_key(|key| match array::array_index(key) {
+            Some(index) => (false, index),
+            None => (true, 0),
+        });
+        keys
+    }
 }
 
 #[derive(Trace, Finalize, Debug)]
 pub enum ObjectPayload {
+    Arguments(function::Arguments),
+    Array(array::Array),
+    Boolean(boolean::Boolean),
+    Date(date::Date),
+    Error(error::Error),
+    Math(math::Math),
     Number(number::Number),
     String(string::String),
     Function(function::Function),
@@ -67,106 +255,171 @@ impl Default for PreferredType {
 }
 
 pub trait Objectable {
-    fn Get(&self, prop: &String) -> Value;
-    fn Put(&mut self, key: String, value: Value);
-    fn CanPut(&self, key: &String) -> bool;
-    fn HasProperty(&self, key: &String) -> bool;
-    fn Construct(&self, _vm: &mut VM, _args: &[Value]) -> JSResult;
-    fn Call(&self, _vm: &mut VM, _args: &[Value]) -> JSResult;
+    fn Get(&self, key: &JSString, vm: &mut VM) -> JSResult;
+    // [[Get]] with the this value of getters, which differs for super.name
+    fn GetWithReceiver(&self, key: &JSString, receiver: Value, vm: &mut VM) -> JSResult;
+    fn Put(&self, key: JSString, value: Value, vm: &mut VM) -> JSResult;
+    fn CanPut(&self, key: &JSString) -> bool;
+    fn HasProperty(&self, key: &JSString) -> bool;
+    fn HasInstance(&self, value: &Value, vm: &mut VM) -> JSResult;
+    fn Construct(&self, vm: &mut VM, args: &[Value]) -> JSResult;
+    fn Call(&self, vm: &mut VM, this: Value, args: &[Value]) -> JSResult;
     fn valueOf(&self, vm: &mut VM) -> JSResult;
-    fn toString(&self, gcobj: &GcObject, vm: &mut VM) -> JSResult;
-    fn DefaultValue(&self, hint: Option<PreferredType>, gcobj: &GcObject, vm: &mut VM) -> JSResult;
+    fn toString(&self, vm: &mut VM) -> JSResult;
+    fn DefaultValue(&self, hint: Option<PreferredType>, vm: &mut VM) -> JSResult;
 }
 
-impl Objectable for Object {
-    fn Get(&self, key: &String) -> Value {
-        match self.props.get(key) {
-            Some(Property { value, .. }) => value.clone(),
-            None => {
-                if let Some(ref proto) = self.__proto__ {
-                    proto.borrow().Get(key)
-                } else {
-                    Value::default()
-                }
-            }
-        }
+// Implemented on the handle rather than on `Object`: getters, setters and
+// functions run user code, which must be able to borrow the object again.
+// Each method holds the borrow only while it reads the object.
+impl Objectable for GcObject {
+    fn Get(&self, key: &JSString, vm: &mut VM) -> JSResult {
+        self.GetWithReceiver(key, self.clone().into(), vm)
     }
-    fn Put(&mut self, key: String, value: Value) {
-        self.props.insert(key, Property::new(value));
+    fn GetWithReceiver(&self, key: &JSString, receiver: Value, vm: &mut VM) -> JSResult {
+        let prop = self.borrow().GetProperty(key);
+        match prop {
+            Some(ref prop) => match prop.accessor {
+                Some(Accessor {
+                    get: Value::Object(ref getter),
+                    ..
+                }) => getter.Call(vm, receiver, &[]),
+                Some(_) => Ok(Value::Undefined),
+                None => Ok(prop.value.clone()),
+            },
+            None => Ok(Value::Undefined),
+        }
     }
-    fn CanPut(&self, key: &String) -> bool {
-        if let Some(prop) = self.props.get(key) {
-            if prop.read_only {
-                false
-            } else {
-                true
-            }
-        } else {
-            if let Some(ref proto) = self.__proto__ {
-                proto.borrow().CanPut(key)
-            } else {
-                true
+    fn Put(&self, key: JSString, value: Value, vm: &mut VM) -> JSResult {
+        let accessor = match self.borrow().GetProperty(&key) {
+            Some(ref prop) => prop.accessor.clone(),
+            None => None,
+        };
+        if let Some(ref accessor) = accessor {
+            if let Value::Object(ref setter) = accessor.set {
+                setter.Call(vm, self.clone().into(), &[value])?;
             }
+            return Ok(Value::Undefined);
         }
-    }
-    fn HasProperty(&self, key: &String) -> bool {
-        match self.props.get(key) {
-            Some(_) => true,
-            _ => false,
+        if self.CanPut(&key) {
+            self.borrow_mut().PutValue(key, value);
         }
+        Ok(Value::Undefined)
     }
-    fn Construct(&self, vm: &mut VM, args: &[Value]) -> JSResult {
-        match &self.payload {
-            // ObjectPayload::PrimFunction(o) => o.Call(vm, args),
-            ObjectPayload::Function(o) => o.Construct(vm, args),
-            _ => Err("Object not constructible".into()),
+    fn CanPut(&self, key: &JSString) -> bool {
+        let object = self.borrow();
+        if let Some(prop) = object.GetOwnProperty(key) {
+            return match prop.accessor {
+                Some(ref accessor) => accessor.set != Value::Undefined,
+                None => !prop.read_only,
+            };
         }
-    }
-    fn Call(&self, vm: &mut VM, args: &[Value]) -> JSResult {
-        match &self.payload {
-            ObjectPayload::Function(o) => o.Call(vm, args),
-            _ => Err("Object not callable".into()),
+        // An inherited read-only property also prevents the put (ES5 8.12.4)
+        let inherited = match object.__proto__ {
+            Some(ref proto) => proto.borrow().GetProperty(key),
+            None => None,
+        };
+        match inherited {
+            Some(ref prop) => match prop.accessor {
+                Some(ref accessor) => accessor.set != Value::Undefined,
+                None => object.extensible && !prop.read_only,
+            },
+            None => object.extensible,
         }
     }
-    fn valueOf(&self, vm: &mut VM) -> JSResult {
-        match &self.payload {
-            ObjectPayload::Number(o) => Ok(o.valueOf()),
-            ObjectPayload::String(o) => Ok(o.valueOf()),
-            ObjectPayload::Regular(o) => o.valueOf(self, vm),
-            _ => Err("couldn't compute valueOf".into()),
-        }
-    }
-    fn toString(&self, gcobj: &GcObject, vm: &mut VM) -> JSResult {
-        match &self.payload {
-            ObjectPayload::Number(o) => Ok(o.toString()),
-            ObjectPayload::String(o) => Ok(o.toString()),
-            ObjectPayload::Regular(o) => o.toString(gcobj, vm),
-            _ => Err("couldn't compute toString".into()),
-        }
-    }
-    fn DefaultValue(&self, hint: Option<PreferredType>, gcobj: &GcObject, vm: &mut VM) -> JSResult {
-        let hint = hint.unwrap_or_default();
-        let string = self.toString(gcobj, vm);
-        let value = self.valueOf(vm);
-        if hint == PreferredType::String {
-            match string {
-                Err(_) | Ok(Value::Object(_)) => (),
-                Ok(v) => return Ok(v),
+    fn HasProperty(&self, key: &JSString) -> bool {
+        self.borrow().GetProperty(key).is_some()
+    }
+    fn HasInstance(&self, value: &Value, vm: &mut VM) -> JSResult {
+        let function = match self.borrow().payload {
+            ObjectPayload::Function(ref f) => f.clone(),
+            _ => {
+                return Err(vm.ctx.new_Error(
+                    error::ErrorKind::TypeError,
+                    "Right-hand side of 'instanceof' is not callable",
+                ))
             }
-            match value {
-                Err(_) | Ok(Value::Object(_)) => Err("runtime error".into()),
-                Ok(v) => Ok(v),
+        };
+        function.HasInstance(self, vm, value)
+    }
+    fn Construct(&self, vm: &mut VM, args: &[Value]) -> JSResult {
+        let function = match self.borrow().payload {
+            ObjectPayload::Function(ref f) => f.clone(),
+            _ => {
+                let message = format!("#<{}> is not a constructor", self.borrow().Class());
+                return Err(vm.ctx.new_Error(error::ErrorKind::TypeError, &message));
             }
-        } else {
-            match value {
-                Err(_) | Ok(Value::Object(_)) => (),
-                Ok(v) => return Ok(v),
+        };
+        function.Construct(self, vm, args, self)
+    }
+    fn Call(&self, vm: &mut VM, this: Value, args: &[Value]) -> JSResult {
+        let function = match self.borrow().payload {
+            ObjectPayload::Function(ref f) => f.clone(),
+            _ => {
+                let message = format!("#<{}> is not a function", self.borrow().Class());
+                return Err(vm.ctx.new_Error(error::ErrorKind::TypeError, &message));
+            }
+        };
+        function.Call(self, vm, this, args)
+    }
+    fn valueOf(&self, vm: &mut VM) -> JSResult {
+        let value = match self.borrow().payload {
+            ObjectPayload::Boolean(ref o) => Some(o.valueOf()),
+            ObjectPayload::Date(ref o) => Some(o.valueOf()),
+            ObjectPayload::Number(ref o) => Some(o.valueOf()),
+            ObjectPayload::String(ref o) => Some(o.valueOf()),
+            ObjectPayload::Arguments(_)
+            | ObjectPayload::Array(_)
+            | ObjectPayload::Error(_)
+            | ObjectPayload::Function(_)
+            | ObjectPayload::Math(_)
+            | ObjectPayload::Regular(_) => None,
+        };
+        match value {
+            Some(value) => Ok(value),
+            None => object::Regular.valueOf(self, vm),
+        }
+    }
+    fn toString(&self, vm: &mut VM) -> JSResult {
+        let string = match self.borrow().payload {
+            ObjectPayload::Boolean(ref o) => Some(o.toString()),
+            ObjectPayload::Date(ref o) => Some(o.toString(vm)),
+            ObjectPayload::Number(ref o) => Some(o.toString()),
+            ObjectPayload::String(ref o) => Some(o.toString()),
+            ObjectPayload::Arguments(_)
+            | ObjectPayload::Array(_)
+            | ObjectPayload::Error(_)
+            | ObjectPayload::Function(_)
+            | ObjectPayload::Math(_)
+            | ObjectPayload::Regular(_) => None,
+        };
+        match string {
+            Some(string) => Ok(string),
+            None if matches!(self.borrow().payload, ObjectPayload::Error(_)) => {
+                error::Error.toString(self, vm)
             }
-            match string {
-                Err(_) | Ok(Value::Object(_)) => Err("runtime error".into()),
-                Ok(v) => Ok(v),
+            None => object::Regular.toString(self, vm),
+        }
+    }
+    fn DefaultValue(&self, hint: Option<PreferredType>, vm: &mut VM) -> JSResult {
+        let hint = match (hint, &self.borrow().payload) {
+            (None, ObjectPayload::Date(_)) => PreferredType::String,
+            (hint, _) => hint.unwrap_or_default(),
+        };
+        let conversions: [fn(&GcObject, &mut VM) -> JSResult; 2] = match hint {
+            PreferredType::String => [Objectable::toString, Objectable::valueOf],
+            PreferredType::Number => [Objectable::valueOf, Objectable::toString],
+        };
+        for conversion in conversions.iter() {
+            match conversion(self, vm)? {
+                Value::Object(_) => (),
+                v => return Ok(v),
             }
         }
+        Err(vm.ctx.new_Error(
+            error::ErrorKind::TypeError,
+            "Cannot convert object to primitive value",
+        ))
     }
 }
 
@@ -183,13 +436,14 @@ macro_rules! js_impl {
         $(
             $x
             let prop = Property {
-                value: ctx.new_BuiltinFunction(stringify!($name), $name, 0),
+                value: ctx.new_BuiltinFunction(stringify!($name), $name, $l),
+                accessor: None,
                 read_only: true,
                 dont_enum: true,
                 internal: true,
                 dont_delete: true,
             };
-            props.insert (stringify!($name).to_string(), prop);
+            props.insert (stringify!($name).into(), prop);
         )*
             props
         }
@@ -1,3 +1,4 @@
+use super::error::ErrorKind;
 use super::*;
 
 #[derive(Trace, Finalize, Debug, Clone)]
@@ -12,33 +13,19 @@ impl Number {
         self.value.into()
     }
     pub fn toString(&self) -> Value {
-        self.value.to_string().into()
+        to_string(self.value).into()
     }
 }
 
-macro_rules! extract_number {
-    ($x:ident) => {
-        if let Value::Object(ref $x) = $x {
-            if let ObjectPayload::Number(ref n) = $x.borrow().payload {
-                n.clone()
-            } else {
-                return Err("Expect number".into());
-            }
-        } else {
-            panic!("Expected object")
-        }
-    };
-}
-
 // JS Primitives
-pub fn function(vm: &mut VM, args: &[Value]) -> JSResult {
+pub fn function(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
     Ok(match args.len() {
         0 => Value::Number(0.),
         _ => args[0].ToNumber(vm)?,
     })
 }
 
-pub fn constructor(vm: &mut VM, args: &[Value]) -> JSResult {
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
     match args.len() {
         0 => Ok(vm.ctx.new_Number(0.)),
         _ => match args[0].ToNumber(vm) {
@@ -56,16 +43,420 @@ pub fn constructor(vm: &mut VM, args: &[Value]) -> JSResult {
 
 use crate::js_impl;
 use crate::vm::context::Context;
+use crate::vm::jsstring::JSString;
+
+// StringToNumber (ECMA-262 9.3.1): NaN unless the string, less surrounding
+// whitespace, is a numeric literal in its entirety
+pub fn from_string(s: &JSString) -> f64 {
+    let units = s.units();
+    let start = units
+        .iter()
+        .position(|c| !string::is_whitespace(*c))
+        .unwrap_or(units.len());
+    let end = units
+        .iter()
+        .rposition(|c| !string::is_whitespace(*c))
+        .map_or(start, |k| k + 1);
+    let s = match std::string::String::from_utf16(&units[start..end]) {
+        Ok(s) => s,
+        Err(_) => return f64::NAN,
+    };
+    if s.is_empty() {
+        return 0.;
+    }
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        return match radix_prefix(hex, 16) {
+            len if len > 0 && len == hex.len() => radix_value(hex, 16),
+            _ => f64::NAN,
+        };
+    }
+    let (sign, unsigned) = split_sign(&s);
+    if unsigned == "Infinity" {
+        return sign * f64::INFINITY;
+    }
+    match decimal_prefix(unsigned) {
+        len if len > 0 && len == unsigned.len() => sign * unsigned.parse::<f64>().unwrap(),
+        _ => f64::NAN,
+    }
+}
+
+pub fn split_sign(s: &str) -> (f64, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        (-1., rest)
+    } else {
+        (1., s.strip_prefix('+').unwrap_or(s))
+    }
+}
+
+// Length of the longest prefix of `s` that is a StrUnsignedDecimalLiteral
+pub fn decimal_prefix(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let digits_from = |k: usize| bytes[k..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let int_digits = digits_from(0);
+    let mut len = int_digits;
+    let mut frac_digits = 0;
+    if bytes.get(len) == Some(&b'.') {
+        frac_digits = digits_from(len + 1);
+        if int_digits + frac_digits > 0 {
+            len += 1 + frac_digits;
+        }
+    }
+    if int_digits + frac_digits == 0 {
+        return 0;
+    }
+    if let Some(b'e') | Some(b'E') = bytes.get(len) {
+        let sign = matches!(bytes.get(len + 1), Some(b'+') | Some(b'-')) as usize;
+        let exp_digits = digits_from(len + 1 + sign);
+        if exp_digits > 0 {
+            len += 1 + sign + exp_digits;
+        }
+    }
+    len
+}
+
+// Length of the longest prefix of `s` made of digits in `radix`
+pub fn radix_prefix(s: &str, radix: u32) -> usize {
+    s.chars().take_while(|c| c.is_digit(radix)).count()
+}
+
+pub fn radix_value(digits: &str, radix: u32) -> f64 {
+    if radix == 10 {
+        return digits.parse::<f64>().unwrap_or(f64::NAN);
+    }
+    digits.chars().fold(0., |acc, c| {
+        acc * radix as f64 + c.to_digit(radix).unwrap() as f64
+    })
+}
+
+// Number::toString (ECMA-262 9.8.1), built on the shortest round-tripping digits
+pub fn to_string(value: f64) -> std::string::String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value == 0. {
+        return "0".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0. { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    let sign = if value < 0. { "-" } else { "" };
+    let (digits, exp) = shortest_digits(value.abs());
+    let k = digits.len() as i32;
+    let n = exp + 1;
+    let res = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        exponential(&digits, n - 1)
+    };
+    format!("{}{}", sign, res)
+}
+
+// "d.ddde+x"
+fn exponential(digits: &str, exp: i32) -> std::string::String {
+    let sign = if exp < 0 { '-' } else { '+' };
+    if digits.len() == 1 {
+        format!("{}e{}{}", digits, sign, exp.abs())
+    } else {
+        format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, exp.abs())
+    }
+}
+
+// Significant digits and decimal exponent of the shortest representation of x > 0
+fn shortest_digits(x: f64) -> (std::string::String, i32) {
+    split_exponential(&format!("{:e}", x))
+}
+
+fn split_exponential(s: &str) -> (std::string::String, i32) {
+    let idx = s.find('e').expect("exponential format");
+    let digits = s[..idx].replace('.', "");
+    (digits, s[idx + 1..].parse().expect("exponent"))
+}
+
+// Adds one unit in the last place, returns true when the digits overflow (999 -> 000)
+fn increment(digits: &mut [u8]) -> bool {
+    for d in digits.iter_mut().rev() {
+        if *d == b'9' {
+            *d = b'0';
+        } else {
+            *d += 1;
+            return false;
+        }
+    }
+    true
+}
+
+// The `count` leading significant digits of x >= 0, rounding ties away from zero.
+// Every double has a finite decimal expansion, so the wide format below is exact.
+fn precise_digits(x: f64, count: usize) -> (std::string::String, i32) {
+    let (all, mut exp) = split_exponential(&format!("{:.1100e}", x));
+    let mut digits = all.into_bytes();
+    let round_up = digits.get(count).is_some_and(|d| *d >= b'5');
+    digits.resize(count, b'0');
+    if round_up && increment(&mut digits) {
+        digits.insert(0, b'1');
+        digits.truncate(count);
+        exp += 1;
+    }
+    (std::string::String::from_utf8(digits).unwrap(), exp)
+}
+
+// Digits of x >= 0 rounded to `fraction` places, ties away from zero
+fn fixed(x: f64, fraction: usize) -> std::string::String {
+    let exact = format!("{:.1100}", x);
+    let point = exact.find('.').expect("fixed format");
+    let mut digits = exact[..point + 1 + fraction].replace('.', "").into_bytes();
+    if exact.as_bytes()[point + 1 + fraction] >= b'5' && increment(&mut digits) {
+        digits.insert(0, b'1');
+    }
+    let mut res = std::string::String::from_utf8(digits).unwrap();
+    if fraction > 0 {
+        res.insert(res.len() - fraction, '.');
+    }
+    res
+}
+
+fn to_radix_string(value: f64, radix: u32) -> std::string::String {
+    if !value.is_finite() {
+        return to_string(value);
+    }
+    let chars = |d: u32| std::char::from_digit(d, radix).expect("digit");
+    let negative = value < 0.;
+    let value = value.abs();
+    let mut integer = value.floor();
+    let mut fraction = value - integer;
+    let radix_f = radix as f64;
+    // Half the distance to the next double, digits below it are noise
+    let mut delta = (0.5 * (f64::from_bits(value.to_bits() + 1) - value)).max(f64::from_bits(1));
+    let mut fraction_digits = Vec::new();
+    if fraction >= delta {
+        loop {
+            fraction *= radix_f;
+            delta *= radix_f;
+            let digit = fraction.floor() as u32;
+            fraction_digits.push(digit);
+            fraction -= digit as f64;
+            if (fraction > 0.5 || (fraction == 0.5 && digit & 1 == 1)) && fraction + delta > 1. {
+                // Round up, propagating the carry into the integer part if needed
+                loop {
+                    match fraction_digits.pop() {
+                        None => {
+                            integer += 1.;
+                            break;
+                        }
+                        Some(d) if d + 1 < radix => {
+                            fraction_digits.push(d + 1);
+                            break;
+                        }
+                        Some(_) => (),
+                    }
+                }
+                break;
+            }
+            if fraction < delta {
+                break;
+            }
+        }
+    }
+    let mut integer_digits = Vec::new();
+    // Integers beyond 2^53 are not exact, their low digits are zeros
+    while integer / radix_f >= 9007199254740992. {
+        integer /= radix_f;
+        integer_digits.push('0');
+    }
+    loop {
+        let remainder = integer % radix_f;
+        integer_digits.push(chars(remainder as u32));
+        integer = (integer - remainder) / radix_f;
+        if integer <= 0. {
+            break;
+        }
+    }
+    let mut res: std::string::String = integer_digits.iter().rev().collect();
+    if negative {
+        res.insert(0, '-');
+    }
+    if !fraction_digits.is_empty() {
+        res.push('.');
+        res.extend(fraction_digits.into_iter().map(chars));
+    }
+    res
+}
+
+fn this_number(vm: &VM, this: &Value) -> Result<f64, Value> {
+    match this {
+        Value::Number(n) => Ok(*n),
+        Value::Object(o) => match o.borrow().payload {
+            ObjectPayload::Number(ref n) => Ok(n.value),
+            _ => Err(vm
+                .ctx
+                .new_Error(ErrorKind::TypeError, "`this` is not a Number")),
+        },
+        _ => Err(vm
+            .ctx
+            .new_Error(ErrorKind::TypeError, "`this` is not a Number")),
+    }
+}
+
+fn arg_integer(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    match args.get(idx).cloned().unwrap_or_default().ToNumber(vm)? {
+        Value::Number(n) if n.is_nan() => Ok(0.),
+        Value::Number(n) => Ok(n.trunc()),
+        _ => Err("Fatal Error: ToNumber didn't return Value::Number".into()),
+    }
+}
+
+fn is_undefined(args: &[Value], idx: usize) -> bool {
+    matches!(args.get(idx), None | Some(Value::Undefined))
+}
+
+fn number_arg(args: &[Value]) -> Option<f64> {
+    match args.first() {
+        Some(Value::Number(n)) => Some(*n),
+        _ => None,
+    }
+}
+
+fn constant(value: f64) -> Property {
+    Property::new(value.into())
+        .read_only()
+        .dont_enum()
+        .dont_delete()
+}
+
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.;
+
+pub fn get_function_props(ctx: &Context) -> JSDict {
+    fn isFinite(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(number_arg(args).is_some_and(f64::is_finite).into())
+    }
+    fn isNaN(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(number_arg(args).is_some_and(f64::is_nan).into())
+    }
+    fn isInteger(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(number_arg(args)
+            .is_some_and(|n| n.is_finite() && n.trunc() == n)
+            .into())
+    }
+    fn isSafeInteger(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        Ok(number_arg(args)
+            .is_some_and(|n| n.trunc() == n && n.abs() <= MAX_SAFE_INTEGER)
+            .into())
+    }
+    let mut props = JSDict::new();
+    props.insert("MAX_VALUE".into(), constant(f64::MAX));
+    props.insert("MIN_VALUE".into(), constant(f64::from_bits(1)));
+    props.insert("NaN".into(), constant(f64::NAN));
+    props.insert("POSITIVE_INFINITY".into(), constant(f64::INFINITY));
+    props.insert("NEGATIVE_INFINITY".into(), constant(f64::NEG_INFINITY));
+    props.insert("EPSILON".into(), constant(f64::EPSILON));
+    props.insert("MAX_SAFE_INTEGER".into(), constant(MAX_SAFE_INTEGER));
+    props.insert("MIN_SAFE_INTEGER".into(), constant(-MAX_SAFE_INTEGER));
+    let functions: [(&'static str, RJSFunc); 4] = [
+        ("isFinite", isFinite),
+        ("isNaN", isNaN),
+        ("isInteger", isInteger),
+        ("isSafeInteger", isSafeInteger),
+    ];
+    for (name, func) in functions.iter() {
+        props.insert(
+            (*name).into(),
+            Property::new(ctx.new_BuiltinFunction(name, *func, 1)).dont_enum(),
+        );
+    }
+    props
+}
 
 js_impl! {
-    #[prop(name=valueOf,length=1)]
-    fn valueOf(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let this = vm.get_this();
-        Ok(extract_number!(this).value.into())
+    #[prop(name=valueOf,length=0)]
+    fn valueOf(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(this_number(vm, this)?.into())
     },
     #[prop(name=toString, length=1)]
-    fn toString(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let this = vm.get_this();
-        Ok(extract_number!(this).value.to_string().into())
+    fn toString(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let x = this_number(vm, this)?;
+        let radix = if is_undefined(args, 0) { 10. } else { arg_integer(vm, args, 0)? };
+        if !(2. ..=36.).contains(&radix) {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "toString() radix must be between 2 and 36"));
+        }
+        if radix == 10. {
+            return Ok(to_string(x).into());
+        }
+        Ok(to_radix_string(x, radix as u32).into())
+    },
+    #[prop(name=toLocaleString, length=0)]
+    fn toLocaleString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        Ok(to_string(this_number(vm, this)?).into())
+    },
+    #[prop(name=toFixed, length=1)]
+    fn toFixed(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let x = this_number(vm, this)?;
+        let f = arg_integer(vm, args, 0)?;
+        if !(0. ..=100.).contains(&f) {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "toFixed() digits argument must be between 0 and 100"));
+        }
+        if !x.is_finite() || x.abs() >= 1e21 {
+            return Ok(to_string(x).into());
+        }
+        let sign = if x < 0. { "-" } else { "" };
+        Ok(format!("{}{}", sign, fixed(x.abs(), f as usize)).into())
+    },
+    #[prop(name=toExponential, length=1)]
+    fn toExponential(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let x = this_number(vm, this)?;
+        let f = arg_integer(vm, args, 0)?;
+        if !x.is_finite() {
+            return Ok(to_string(x).into());
+        }
+        if !(0. ..=100.).contains(&f) {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "toExponential() argument must be between 0 and 100"));
+        }
+        let sign = if x < 0. { "-" } else { "" };
+        let (digits, exp) = if x == 0. {
+            let count = if is_undefined(args, 0) { 1 } else { f as usize + 1 };
+            ("0".repeat(count), 0)
+        } else if is_undefined(args, 0) {
+            shortest_digits(x.abs())
+        } else {
+            precise_digits(x.abs(), f as usize + 1)
+        };
+        Ok(format!("{}{}", sign, exponential(&digits, exp)).into())
+    },
+    #[prop(name=toPrecision, length=1)]
+    fn toPrecision(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let x = this_number(vm, this)?;
+        if is_undefined(args, 0) {
+            return Ok(to_string(x).into());
+        }
+        let p = arg_integer(vm, args, 0)?;
+        if !x.is_finite() {
+            return Ok(to_string(x).into());
+        }
+        if !(1. ..=100.).contains(&p) {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "toPrecision() argument must be between 1 and 100"));
+        }
+        let p = p as usize;
+        let sign = if x < 0. { "-" } else { "" };
+        let (digits, exp) = if x == 0. {
+            ("0".repeat(p), 0)
+        } else {
+            precise_digits(x.abs(), p)
+        };
+        let res = if exp < -6 || exp >= p as i32 {
+            exponential(&digits, exp)
+        } else if exp >= 0 {
+            let point = exp as usize + 1;
+            if point == p {
+                digits
+            } else {
+                format!("{}.{}", &digits[..point], &digits[point..])
+            }
+        } else {
+            format!("0.{}{}", "0".repeat((-exp - 1) as usize), digits)
+        };
+        Ok(format!("{}{}", sign, res).into())
     }
 }
@@ -1,35 +1,38 @@
+use super::error::ErrorKind;
 use super::*;
+use crate::vm::context::Context;
 
 #[derive(Trace, Finalize, Debug)]
 pub struct Regular;
 
-impl Regular {
-    pub fn valueOf(&self, obj: &Object, vm: &mut VM) -> JSResult {
-        if let Some(ref proto) = obj.__proto__ {
-            if let Value::Object(ref valueOf) = proto.borrow().Get(&"valueOf".to_string()) {
-                return valueOf.borrow().Call(vm, &[]);
-            }
+// Calls the object's method `name`, if callable. Otherwise the object itself is
+// returned, which [[DefaultValue]] skips as not being a primitive.
+fn invoke(gcobj: &GcObject, name: &str, vm: &mut VM) -> JSResult {
+    match gcobj.Get(&name.into(), vm)? {
+        Value::Object(ref method)
+            if matches!(method.borrow().payload, ObjectPayload::Function(_)) =>
+        {
+            method.Call(vm, gcobj.into(), &[])
         }
-        panic!("Fatal Error: valueOf not found");
+        _ => Ok(gcobj.into()),
+    }
+}
+
+impl Regular {
+    pub fn valueOf(&self, gcobj: &GcObject, vm: &mut VM) -> JSResult {
+        invoke(gcobj, "valueOf", vm)
     }
     pub fn toString(&self, gcobj: &GcObject, vm: &mut VM) -> JSResult {
-        if let Some(ref proto) = gcobj.borrow().__proto__ {
-            if let Value::Object(ref toString) = proto.borrow().Get(&"toString".to_string()) {
-                vm.push_this(gcobj.into());
-                let res = toString.borrow().Call(vm, &[]);
-                let _ = vm.pop_this();
-                return res;
-            }
-        }
-        panic!("Fatal Error: toString not found");
+        invoke(gcobj, "toString", vm)
     }
 }
 
 // JS Primitives
-pub fn constructor(_vm: &mut VM, _args: &[Value]) -> JSResult {
-    Err("Not impl".into())
+// new Object (value) behaves as Object (value) for native objects
+pub fn constructor(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    function(vm, this, args)
 }
-pub fn function(vm: &mut VM, args: &[Value]) -> JSResult {
+pub fn function(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
     match args.len() {
         0 => Ok(vm
             .ctx
@@ -45,13 +48,564 @@ pub fn function(vm: &mut VM, args: &[Value]) -> JSResult {
     }
 }
 
-pub fn toString(vm: &mut VM, _args: &[Value]) -> JSResult {
-    if let Value::Object(_) = vm.get_this() {
-        return Ok("[object Object]".into());
+pub fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+    let class = match this {
+        Value::Undefined => "Undefined",
+        Value::Null => "Null",
+        this => this.as_object(vm.ctx).borrow().Class(),
+    };
+    Ok(format!("[object {}]", class).into())
+}
+
+pub fn toLocaleString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+    let toString = to_object(vm, this)?.Get(&"toString".into(), vm)?;
+    match toString {
+        Value::Object(ref f) if matches!(f.borrow().payload, ObjectPayload::Function(_)) => {
+            f.Call(vm, this.clone(), &[])
+        }
+        _ => Err(type_error(vm, "toString is not a function")),
+    }
+}
+
+pub fn hasOwnProperty(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    let key = arg(args, 0).ToPropertyKey(vm)?;
+    let object = to_object(vm, this)?;
+    let has = object.borrow().GetOwnProperty(&key).is_some();
+    Ok(has.into())
+}
+
+pub fn isPrototypeOf(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    let mut proto = match args.first() {
+        Some(Value::Object(o)) => o.borrow().__proto__.clone(),
+        _ => return Ok(false.into()),
+    };
+    let object = to_object(vm, this)?;
+    while let Some(o) = proto {
+        if Gc::ptr_eq(&o, &object) {
+            return Ok(true.into());
+        }
+        proto = o.borrow().__proto__.clone();
+    }
+    Ok(false.into())
+}
+
+pub fn propertyIsEnumerable(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+    let key = arg(args, 0).ToPropertyKey(vm)?;
+    let object = to_object(vm, this)?;
+    let enumerable = matches!(object.borrow().GetOwnProperty(&key), Some(prop) if !prop.dont_enum);
+    Ok(enumerable.into())
+}
+
+pub fn valueOf(_vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+    Ok(this.clone())
+}
+
+fn type_error(vm: &VM, message: &str) -> Value {
+    vm.ctx.new_Error(ErrorKind::TypeError, message)
+}
+
+fn arg(args: &[Value], idx: usize) -> Value {
+    args.get(idx).cloned().unwrap_or_default()
+}
+
+// ToObject, which throws for undefined and null
+fn to_object(vm: &VM, value: &Value) -> Result<GcObject, Value> {
+    match value {
+        Value::Undefined | Value::Null => {
+            Err(type_error(vm, "Cannot convert undefined or null to object"))
+        }
+        _ => Ok(value.as_object(vm.ctx)),
+    }
+}
+
+// The [[Prototype]] argument of Object.create and Object.setPrototypeOf
+fn to_prototype(vm: &mut VM, value: &Value) -> Result<Option<GcObject>, Value> {
+    match value {
+        Value::Object(o) => Ok(Some(o.clone())),
+        Value::Null => Ok(None),
+        _ => {
+            let message = format!(
+                "Object prototype may only be an Object or null: {}",
+                value.ToString(vm)?
+            );
+            Err(type_error(vm, &message))
+        }
+    }
+}
+
+// A property descriptor (ES5 8.10); absent fields are None
+#[derive(Default)]
+struct PropertyDescriptor {
+    value: Option<Value>,
+    writable: Option<bool>,
+    get: Option<Value>,
+    set: Option<Value>,
+    enumerable: Option<bool>,
+    configurable: Option<bool>,
+}
+
+impl PropertyDescriptor {
+    fn is_accessor(&self) -> bool {
+        self.get.is_some() || self.set.is_some()
+    }
+    fn is_data(&self) -> bool {
+        self.value.is_some() || self.writable.is_some()
+    }
+}
+
+fn is_callable(value: &Value) -> bool {
+    matches!(value, Value::Object(o) if matches!(o.borrow().payload, ObjectPayload::Function(_)))
+}
+
+// ToPropertyDescriptor (ES5 8.10.5)
+fn to_descriptor(vm: &mut VM, value: &Value) -> Result<PropertyDescriptor, Value> {
+    let object = match value {
+        Value::Object(o) => o,
+        _ => {
+            let message = format!(
+                "Property description must be an object: {}",
+                value.ToString(vm)?
+            );
+            return Err(type_error(vm, &message));
+        }
+    };
+    let mut field = |name: &str| -> Result<Option<Value>, Value> {
+        let name = JSString::from(name);
+        if object.borrow().GetProperty(&name).is_none() {
+            return Ok(None);
+        }
+        Ok(Some(object.Get(&name, vm)?))
+    };
+    let desc = PropertyDescriptor {
+        enumerable: field("enumerable")?.map(|v| v.into()),
+        configurable: field("configurable")?.map(|v| v.into()),
+        value: field("value")?,
+        writable: field("writable")?.map(|v| v.into()),
+        get: field("get")?,
+        set: field("set")?,
+    };
+    for (kind, accessor) in [("Getter", &desc.get), ("Setter", &desc.set)].iter() {
+        match accessor {
+            Some(f) if *f != Value::Undefined && !is_callable(f) => {
+                let message = format!("{} must be a function: {}", kind, f.ToString(vm)?);
+                return Err(type_error(vm, &message));
+            }
+            _ => (),
+        }
+    }
+    if desc.is_accessor() && desc.is_data() {
+        return Err(type_error(
+            vm,
+            "Invalid property descriptor. Cannot both specify accessors and a value or writable attribute",
+        ));
+    }
+    Ok(desc)
+}
+
+// FromPropertyDescriptor (ES5 8.10.4)
+fn from_descriptor(vm: &VM, prop: &Property) -> Value {
+    let descriptor = vm.ctx.new_Object(Some(vm.ctx.Object_prototype.clone()));
+    {
+        let object = descriptor.unwrap_object();
+        let mut object = object.borrow_mut();
+        match prop.accessor {
+            Some(ref accessor) => {
+                object.PutValue("get".into(), accessor.get.clone());
+                object.PutValue("set".into(), accessor.set.clone());
+            }
+            None => {
+                object.PutValue("value".into(), prop.value.clone());
+                object.PutValue("writable".into(), (!prop.read_only).into());
+            }
+        }
+        object.PutValue("enumerable".into(), (!prop.dont_enum).into());
+        object.PutValue("configurable".into(), (!prop.dont_delete).into());
+    }
+    descriptor
+}
+
+// [[DefineOwnProperty]] (ES5 8.12.9), always throwing on rejection
+fn define_own_property(
+    vm: &VM,
+    object: &GcObject,
+    key: JSString,
+    desc: PropertyDescriptor,
+) -> Result<(), Value> {
+    let mut object = object.borrow_mut();
+    let is_array = matches!(object.payload, ObjectPayload::Array(_));
+    // The index properties of a String object are stored once redefined
+    if !object.props.contains_key(&key) {
+        if let Some(prop) = object.GetOwnProperty(&key) {
+            object.props.insert(key.clone(), prop);
+        }
+    }
+    match object.props.get_mut(&key) {
+        None => {
+            if !object.extensible {
+                let message = format!("Cannot define property {}, object is not extensible", key);
+                return Err(type_error(vm, &message));
+            }
+            let mut prop = match desc.is_accessor() {
+                true => Property::accessor(
+                    desc.get.clone().unwrap_or_default(),
+                    desc.set.clone().unwrap_or_default(),
+                ),
+                false => Property::new(Value::Undefined).read_only(),
+            };
+            prop.read_only &= !desc.writable.unwrap_or(false);
+            prop.dont_enum = !desc.enumerable.unwrap_or(false);
+            prop.dont_delete = !desc.configurable.unwrap_or(false);
+            object.props.insert(key.clone(), prop);
+        }
+        Some(prop) => {
+            let rejected = prop.dont_delete
+                && (desc.configurable == Some(true)
+                    || desc.enumerable == Some(prop.dont_enum)
+                    || match prop.accessor {
+                        Some(ref accessor) => {
+                            desc.is_data()
+                                || matches!(desc.get, Some(ref get) if !get.SameValue(&accessor.get))
+                                || matches!(desc.set, Some(ref set) if !set.SameValue(&accessor.set))
+                        }
+                        None => {
+                            desc.is_accessor()
+                                || (prop.read_only
+                                    && (desc.writable == Some(true)
+                                        || matches!(desc.value, Some(ref v) if !v.SameValue(&prop.value))))
+                        }
+                    });
+            if rejected {
+                return Err(type_error(
+                    vm,
+                    &format!("Cannot redefine property: {}", key),
+                ));
+            }
+            // Switching between a data and an accessor property keeps only the
+            // enumerable and configurable attributes
+            if desc.is_accessor() && prop.accessor.is_none() {
+                prop.value = Value::Undefined;
+                prop.read_only = false;
+                prop.accessor = Some(Accessor {
+                    get: Value::Undefined,
+                    set: Value::Undefined,
+                });
+            } else if desc.is_data() && prop.accessor.is_some() {
+                prop.read_only = true;
+                prop.accessor = None;
+            }
+            if let Some(ref mut accessor) = prop.accessor {
+                if let Some(ref get) = desc.get {
+                    accessor.get = get.clone();
+                }
+                if let Some(ref set) = desc.set {
+                    accessor.set = set.clone();
+                }
+            }
+            if let Some(writable) = desc.writable {
+                prop.read_only = !writable;
+            }
+            if let Some(enumerable) = desc.enumerable {
+                prop.dont_enum = !enumerable;
+            }
+            if let Some(configurable) = desc.configurable {
+                prop.dont_delete = !configurable;
+            }
+        }
+    }
+    if let Some(value) = desc.value {
+        // Arrays keep `length` in sync with their indices
+        if is_array {
+            array::Put(&mut object, key, value);
+        } else if let Some(prop) = object.props.get_mut(&key) {
+            prop.value = value;
+        }
+    }
+    Ok(())
+}
+
+fn define_properties(vm: &mut VM, object: &GcObject, properties: &Value) -> Result<(), Value> {
+    let properties = to_object(vm, properties)?;
+    let mut descriptors = Vec::new();
+    for key in enumerable_own_keys(&properties) {
+        let desc = properties.Get(&key, vm)?;
+        descriptors.push((key, to_descriptor(vm, &desc)?));
+    }
+    for (key, desc) in descriptors {
+        define_own_property(vm, object, key, desc)?;
+    }
+    Ok(())
+}
+
+pub fn enumerable_own_keys(object: &GcObject) -> Vec<JSString> {
+    let object = object.borrow();
+    object
+        .own_keys()
+        .into_iter()
+        .filter(|key| matches!(object.GetOwnProperty(key), Some(prop) if !prop.dont_enum))
+        .collect()
+}
+
+// The names a for-in loop visits: the enumerable properties of an object and
+// its prototypes, skipping those shadowed by a property seen before
+pub fn for_in_keys(object: &GcObject) -> Vec<Value> {
+    let mut seen = std::collections::HashSet::new();
+    let mut keys = Vec::new();
+    let mut next = Some(object.clone());
+    while let Some(object) = next {
+        let object = object.borrow();
+        for key in object.own_keys() {
+            let enumerable = matches!(object.GetOwnProperty(&key), Some(prop) if !prop.dont_enum);
+            if seen.insert(key.clone()) && enumerable {
+                keys.push(key.into());
+            }
+        }
+        next = object.__proto__.clone();
+    }
+    keys
+}
+
+// The enumerable own properties with their values, read through [[Get]]
+pub fn enumerable_own_properties(
+    vm: &mut VM,
+    object: &GcObject,
+) -> Result<Vec<(JSString, Value)>, Value> {
+    let mut properties = Vec::new();
+    for key in enumerable_own_keys(object) {
+        let value = object.Get(&key, vm)?;
+        properties.push((key, value));
+    }
+    Ok(properties)
+}
+
+fn keys(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let keys = enumerable_own_keys(&object)
+        .into_iter()
+        .map(Value::from)
+        .collect();
+    Ok(vm.ctx.new_Array(keys))
+}
+
+fn values(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let values = enumerable_own_properties(vm, &object)?
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+    Ok(vm.ctx.new_Array(values))
+}
+
+fn entries(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let entries = enumerable_own_properties(vm, &object)?
+        .into_iter()
+        .map(|(key, value)| vm.ctx.new_Array(vec![key.into(), value]))
+        .collect();
+    Ok(vm.ctx.new_Array(entries))
+}
+
+fn create(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let proto = to_prototype(vm, &arg(args, 0))?;
+    let object = vm.ctx.new_Object(proto);
+    match arg(args, 1) {
+        Value::Undefined => (),
+        properties => define_properties(vm, &object.unwrap_object(), &properties)?,
+    }
+    Ok(object)
+}
+
+fn getPrototypeOf(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let proto = object.borrow().__proto__.clone();
+    Ok(proto.map_or(Value::Null, Value::from))
+}
+
+fn setPrototypeOf(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let target = arg(args, 0);
+    if let Value::Undefined | Value::Null = target {
+        return Err(type_error(
+            vm,
+            "Object.setPrototypeOf called on null or undefined",
+        ));
+    }
+    let proto = to_prototype(vm, &arg(args, 1))?;
+    let object = match target {
+        Value::Object(ref o) => o,
+        _ => return Ok(target),
+    };
+    let current = object.borrow().__proto__.clone();
+    let unchanged = match (&current, &proto) {
+        (Some(current), Some(proto)) => Gc::ptr_eq(current, proto),
+        (None, None) => true,
+        _ => false,
+    };
+    if unchanged {
+        return Ok(target.clone());
+    }
+    if !object.borrow().extensible {
+        return Err(type_error(vm, "#<Object> is not extensible"));
+    }
+    let mut ancestor = proto.clone();
+    while let Some(o) = ancestor {
+        if Gc::ptr_eq(&o, object) {
+            return Err(type_error(vm, "Cyclic __proto__ value"));
+        }
+        ancestor = o.borrow().__proto__.clone();
+    }
+    object.borrow_mut().__proto__ = proto;
+    Ok(target.clone())
+}
+
+fn defineProperty(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = match args.first() {
+        Some(Value::Object(o)) => o.clone(),
+        _ => return Err(type_error(vm, "Object.defineProperty called on non-object")),
+    };
+    let key = arg(args, 1).ToPropertyKey(vm)?;
+    let desc = to_descriptor(vm, &arg(args, 2))?;
+    define_own_property(vm, &object, key, desc)?;
+    Ok(object.into())
+}
+
+fn defineProperties(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = match args.first() {
+        Some(Value::Object(o)) => o.clone(),
+        _ => {
+            return Err(type_error(
+                vm,
+                "Object.defineProperties called on non-object",
+            ))
+        }
+    };
+    define_properties(vm, &object, &arg(args, 1))?;
+    Ok(object.into())
+}
+
+fn getOwnPropertyDescriptor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let key = arg(args, 1).ToPropertyKey(vm)?;
+    let prop = object.borrow().GetOwnProperty(&key);
+    Ok(match prop {
+        Some(ref prop) => from_descriptor(vm, prop),
+        None => Value::Undefined,
+    })
+}
+
+fn getOwnPropertyNames(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let object = to_object(vm, &arg(args, 0))?;
+    let names = object
+        .borrow()
+        .own_keys()
+        .into_iter()
+        .map(Value::from)
+        .collect();
+    Ok(vm.ctx.new_Array(names))
+}
+
+// Applies `f` to every own property and makes the object non-extensible
+fn restrict(args: &[Value], f: fn(&mut Property)) -> JSResult {
+    let target = arg(args, 0);
+    if let Value::Object(ref o) = target {
+        let mut object = o.borrow_mut();
+        object.props.values_mut().for_each(f);
+        object.extensible = false;
+    }
+    Ok(target)
+}
+
+// Checks that the object is non-extensible and `f` holds for every own property
+fn is_restricted(args: &[Value], f: fn(&Property) -> bool) -> JSResult {
+    Ok(match args.first() {
+        Some(Value::Object(o)) => {
+            let object = o.borrow();
+            !object.extensible && object.props.values().all(f)
+        }
+        _ => true,
     }
-    panic!("Fatal Error: Expected Object");
+    .into())
 }
 
-pub fn valueOf(vm: &mut VM, _args: &[Value]) -> JSResult {
-    Ok(vm.get_this().clone())
+fn freeze(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let target = arg(args, 0);
+    if let Value::Object(ref o) = target {
+        o.borrow_mut().freeze();
+    }
+    Ok(target)
+}
+
+fn isFrozen(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    is_restricted(args, |prop| {
+        (prop.read_only || prop.accessor.is_some()) && prop.dont_delete
+    })
+}
+
+fn seal(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    restrict(args, |prop| prop.dont_delete = true)
+}
+
+fn isSealed(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    is_restricted(args, |prop| prop.dont_delete)
+}
+
+fn preventExtensions(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    restrict(args, |_| ())
+}
+
+fn isExtensible(_vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    Ok(match args.first() {
+        Some(Value::Object(o)) => o.borrow().extensible,
+        _ => false,
+    }
+    .into())
+}
+
+fn assign(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+    let target = to_object(vm, &arg(args, 0))?;
+    for source in args.iter().skip(1) {
+        if let Value::Undefined | Value::Null = source {
+            continue;
+        }
+        for (key, value) in enumerable_own_properties(vm, &source.as_object(vm.ctx))? {
+            if !target.CanPut(&key) {
+                let message = format!(
+                    "Cannot assign to read only property '{}' of object '#<Object>'",
+                    key
+                );
+                return Err(type_error(vm, &message));
+            }
+            target.Put(key, value, vm)?;
+        }
+    }
+    Ok(target.into())
+}
+
+// Function properties of the Object constructor (ES5 15.2.3)
+pub fn get_function_props(ctx: &Context) -> JSDict {
+    let functions: [(&'static str, RJSFunc, usize); 17] = [
+        ("keys", keys, 1),
+        ("values", values, 1),
+        ("entries", entries, 1),
+        ("create", create, 2),
+        ("getPrototypeOf", getPrototypeOf, 1),
+        ("setPrototypeOf", setPrototypeOf, 2),
+        ("defineProperty", defineProperty, 3),
+        ("defineProperties", defineProperties, 2),
+        ("getOwnPropertyDescriptor", getOwnPropertyDescriptor, 2),
+        ("getOwnPropertyNames", getOwnPropertyNames, 1),
+        ("freeze", freeze, 1),
+        ("isFrozen", isFrozen, 1),
+        ("seal", seal, 1),
+        ("isSealed", isSealed, 1),
+        ("preventExtensions", preventExtensions, 1),
+        ("isExtensible", isExtensible, 1),
+        ("assign", assign, 2),
+    ];
+    let mut props = JSDict::new();
+    for (name, func, length) in functions.iter() {
+        props.insert(
+            (*name).into(),
+            Property::new(ctx.new_BuiltinFunction(name, *func, *length)).dont_enum(),
+        );
+    }
+    props
 }
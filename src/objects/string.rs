@@ -1,14 +1,15 @@
+use super::error::ErrorKind;
 use super::*;
 
-type RStr = std::string::String;
+use crate::vm::jsstring::JSString;
 
 #[derive(Trace, Finalize, Debug)]
 pub struct String {
-    value: RStr,
+    value: JSString,
 }
 
 impl String {
-    pub fn new(value: RStr) -> Self {
+    pub fn new(value: JSString) -> Self {
         Self { value }
     }
     pub fn valueOf(&self) -> Value {
@@ -17,9 +18,18 @@ impl String {
     pub fn toString(&self) -> Value {
         self.value.clone().into()
     }
+    // The read-only property of the code unit at an index (ES5 15.5.5.2)
+    pub fn index_property(&self, key: &JSString) -> Option<Property> {
+        let unit = *self.value.units().get(array::array_index(key)? as usize)?;
+        let value = JSString::from(vec![unit]).into();
+        Some(Property::new(value).read_only().dont_delete())
+    }
+    pub fn index_keys(&self) -> impl Iterator<Item = JSString> {
+        (0..self.value.len()).map(|idx| idx.to_string().into())
+    }
 }
 
-pub fn function(vm: &mut VM, args: &[Value]) -> JSResult {
+pub fn function(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
     if args.len() == 0 {
         Ok("".into())
     } else {
@@ -27,60 +37,445 @@ pub fn function(vm: &mut VM, args: &[Value]) -> JSResult {
     }
 }
 
-pub fn constructor(vm: &mut VM, args: &[Value]) -> JSResult {
+pub fn constructor(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
     if args.len() == 0 {
-        Ok(vm.ctx.new_String("".to_string()))
+        Ok(vm.ctx.new_String(JSString::default()))
     } else {
-        let to_string = args[0].ToString(vm);
-        if to_string.is_ok() {
-            Ok(to_string.unwrap().unwrap_string().clone().into())
-        } else {
-            to_string
-        }
+        let s = args[0].ToString(vm)?;
+        Ok(vm.ctx.new_String(s.unwrap_string().clone()))
     }
 }
 
 use crate::js_impl;
 use crate::vm::context::Context;
 
+type Units = Vec<u16>;
+
+fn units(s: &JSString) -> Units {
+    s.units().to_vec()
+}
+
+fn from_units(units: &[u16]) -> Value {
+    JSString::from(units).into()
+}
+
+// Applies a case mapping to every code point, lone surrogates are kept as is
+fn map_case(s: &JSString, map: fn(&char) -> std::string::String) -> Value {
+    let mut res = Units::new();
+    let mut buf = [0u16; 2];
+    for c in std::char::decode_utf16(s.units().iter().cloned()) {
+        match c {
+            Ok(c) => {
+                for c in map(&c).chars() {
+                    res.extend_from_slice(c.encode_utf16(&mut buf));
+                }
+            }
+            Err(e) => res.push(e.unpaired_surrogate()),
+        }
+    }
+    from_units(&res)
+}
+
+// thisStringValue (ES6 21.1.3.25), for the methods that aren't generic
+fn this_string(vm: &VM, this: &Value, method: &str) -> JSResult {
+    match this {
+        Value::String(s) => Ok(s.clone().into()),
+        Value::Object(o) => match o.borrow().payload {
+            ObjectPayload::String(ref s) => Ok(s.valueOf()),
+            _ => Err(not_a_string(vm, method)),
+        },
+        _ => Err(not_a_string(vm, method)),
+    }
+}
+
+fn not_a_string(vm: &VM, method: &str) -> Value {
+    let message = format!("{} requires that 'this' be a String", method);
+    vm.ctx.new_Error(ErrorKind::TypeError, &message)
+}
+
+fn this_units(vm: &mut VM, this: &Value) -> Result<Units, Value> {
+    match this {
+        Value::Undefined | Value::Null => Err(vm.ctx.new_Error(
+            ErrorKind::TypeError,
+            "String.prototype method called on null or undefined",
+        )),
+        _ => Ok(units(this.ToString(vm)?.unwrap_string())),
+    }
+}
+
+fn arg_units(vm: &mut VM, args: &[Value], idx: usize) -> Result<Units, Value> {
+    let arg = args.get(idx).cloned().unwrap_or_default();
+    Ok(units(arg.ToString(vm)?.unwrap_string()))
+}
+
+fn arg_number(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    match args.get(idx).unwrap_or(&Value::Undefined).ToNumber(vm)? {
+        Value::Number(n) => Ok(n),
+        _ => Err("Fatal Error: ToNumber didn't return Value::Number".into()),
+    }
+}
+
+fn arg_integer(vm: &mut VM, args: &[Value], idx: usize) -> Result<f64, Value> {
+    let n = arg_number(vm, args, idx)?;
+    Ok(if n.is_nan() { 0. } else { n.trunc() })
+}
+
+fn is_undefined(args: &[Value], idx: usize) -> bool {
+    matches!(args.get(idx), None | Some(Value::Undefined))
+}
+
+// Clamps a relative index (negative counts from the end) into [0, len]
+fn relative_index(n: f64, len: usize) -> usize {
+    let len = len as f64;
+    (if n < 0. {
+        (len + n).max(0.)
+    } else {
+        n.min(len)
+    }) as usize
+}
+
+fn clamp_index(n: f64, len: usize) -> usize {
+    n.max(0.).min(len as f64) as usize
+}
+
+fn find(haystack: &[u16], needle: &[u16], from: usize) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    (from..=haystack.len() - needle.len()).find(|&k| haystack[k..].starts_with(needle))
+}
+
+fn rfind(haystack: &[u16], needle: &[u16], from: usize) -> Option<usize> {
+    if needle.len() > haystack.len() {
+        return None;
+    }
+    let start = from.min(haystack.len() - needle.len());
+    (0..=start)
+        .rev()
+        .find(|&k| haystack[k..].starts_with(needle))
+}
+
+pub fn is_whitespace(c: u16) -> bool {
+    matches!(
+        c,
+        0x09..=0x0D
+            | 0x20
+            | 0xA0
+            | 0x1680
+            | 0x2000..=0x200A
+            | 0x2028
+            | 0x2029
+            | 0x202F
+            | 0x205F
+            | 0x3000
+            | 0xFEFF
+    )
+}
+
+fn pad(vm: &mut VM, this: &Value, args: &[Value], at_start: bool) -> JSResult {
+    let s = this_units(vm, this)?;
+    let max_len = arg_integer(vm, args, 0)?;
+    let filler = if is_undefined(args, 1) {
+        vec![0x20]
+    } else {
+        arg_units(vm, args, 1)?
+    };
+    if max_len <= s.len() as f64 || filler.is_empty() {
+        return Ok(from_units(&s));
+    }
+    if max_len > MAX_STRING_LENGTH as f64 {
+        return Err(invalid_length(vm));
+    }
+    let fill_len = max_len as usize - s.len();
+    let fill: Units = filler.iter().cycle().take(fill_len).cloned().collect();
+    Ok(if at_start {
+        from_units(&[fill, s].concat())
+    } else {
+        from_units(&[s, fill].concat())
+    })
+}
+
+// The longest string a method creates, in code units, as in V8
+const MAX_STRING_LENGTH: usize = (1 << 29) - 24;
+
+fn invalid_length(vm: &VM) -> Value {
+    vm.ctx
+        .new_Error(ErrorKind::RangeError, "Invalid string length")
+}
+
+// Expands $$, $&, $` and $' in a replacement string
+fn expand_replacement(replacement: &[u16], s: &[u16], pos: usize, matched: &[u16]) -> Units {
+    let dollar = '$' as u16;
+    let mut res = Units::new();
+    let mut idx = 0;
+    while idx < replacement.len() {
+        let c = replacement[idx];
+        match replacement.get(idx + 1) {
+            Some(&next) if c == dollar => {
+                match char::from_u32(next as u32) {
+                    Some('$') => res.push(dollar),
+                    Some('&') => res.extend_from_slice(matched),
+                    Some('`') => res.extend_from_slice(&s[..pos]),
+                    Some('\'') => res.extend_from_slice(&s[pos + matched.len()..]),
+                    _ => {
+                        res.push(c);
+                        idx += 1;
+                        continue;
+                    }
+                }
+                idx += 2;
+            }
+            _ => {
+                res.push(c);
+                idx += 1;
+            }
+        }
+    }
+    res
+}
+
+pub fn get_function_props(ctx: &Context) -> JSDict {
+    fn fromCharCode(vm: &mut VM, _this: &Value, args: &[Value]) -> JSResult {
+        let mut res = Units::new();
+        for idx in 0..args.len() {
+            let n = arg_number(vm, args, idx)?;
+            let n = if n.is_finite() { n.trunc() } else { 0. };
+            res.push((n.rem_euclid(65536.)) as u16);
+        }
+        Ok(from_units(&res))
+    }
+    let mut props = JSDict::new();
+    props.insert(
+        "fromCharCode".into(),
+        Property::new(ctx.new_BuiltinFunction("fromCharCode", fromCharCode, 1)),
+    );
+    props
+}
+
 js_impl! {
     #[prop(name=toString, length=1)]
-    fn toString(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let obj = vm.get_this().as_object(vm.ctx);
-        let ref payload = obj.borrow().payload;
-        match payload {
-            ObjectPayload::String(s) => Ok(s.valueOf()),
-            _ => Err("RuntimeError: `this` is not a String object".into()),
-        }
+    fn toString(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        this_string(vm, this, "String.prototype.toString")
     },
 
     #[prop(name=valueOf, length=1)]
-    fn valueOf(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let obj = vm.get_this().as_object(vm.ctx);
-        let ref payload = obj.borrow().payload;
-        match payload {
-            ObjectPayload::String(s) => Ok(s.valueOf()),
-            _ => Err("RuntimeError: `this` is not a String object".into()),
-        }
+    fn valueOf(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        this_string(vm, this, "String.prototype.valueOf")
     },
 
     #[prop(name=toLowerCase, length=1)]
-    fn toLowerCase(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let obj = vm.get_this().as_object(vm.ctx);
-        let ref payload = obj.borrow().payload;
-        match payload {
-            ObjectPayload::String(String{ref value}) => Ok(value.to_lowercase ().into()),
-            _ => Err("RuntimeError: `this` is not a String object".into()),
-        }
+    fn toLowerCase(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        Ok(map_case(&JSString::from(s), |c| c.to_lowercase().collect()))
     },
 
     #[prop(name=toUpperCase, length=1)]
-    fn toUpperCase(vm: &mut VM, _args: &[Value]) -> JSResult {
-        let obj = vm.get_this().as_object(vm.ctx);
-        let ref payload = obj.borrow().payload;
-        match payload {
-            ObjectPayload::String(String{ref value}) => Ok(value.to_uppercase ().into()),
-            _ => Err("RuntimeError: `this` is not a String object".into()),
+    fn toUpperCase(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        Ok(map_case(&JSString::from(s), |c| c.to_uppercase().collect()))
+    },
+
+    #[prop(name=charAt, length=1)]
+    fn charAt(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let pos = arg_integer(vm, args, 0)?;
+        if pos < 0. || pos >= s.len() as f64 {
+            return Ok("".into());
+        }
+        Ok(from_units(&s[pos as usize..pos as usize + 1]))
+    },
+
+    #[prop(name=charCodeAt, length=1)]
+    fn charCodeAt(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let pos = arg_integer(vm, args, 0)?;
+        if pos < 0. || pos >= s.len() as f64 {
+            return Ok(f64::NAN.into());
+        }
+        Ok((s[pos as usize] as f64).into())
+    },
+
+    #[prop(name=indexOf, length=1)]
+    fn indexOf(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let start = clamp_index(arg_integer(vm, args, 1)?, s.len());
+        Ok(find(&s, &search, start).map_or(-1., |k| k as f64).into())
+    },
+
+    #[prop(name=lastIndexOf, length=1)]
+    fn lastIndexOf(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let pos = arg_number(vm, args, 1)?;
+        let pos = if pos.is_nan() { f64::INFINITY } else { pos.trunc() };
+        let start = clamp_index(pos, s.len());
+        Ok(rfind(&s, &search, start).map_or(-1., |k| k as f64).into())
+    },
+
+    #[prop(name=split, length=2)]
+    fn split(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let limit = if is_undefined(args, 1) {
+            u32::MAX
+        } else {
+            arg_number(vm, args, 1)?.rem_euclid(4294967296.) as u32
+        } as usize;
+        let mut parts = Vec::new();
+        if is_undefined(args, 0) {
+            parts.push(from_units(&s));
+        } else {
+            let sep = arg_units(vm, args, 0)?;
+            if sep.is_empty() {
+                parts.extend(s.iter().map(|c| from_units(&[*c])));
+            } else if !s.is_empty() || find(&s, &sep, 0).is_none() {
+                let mut start = 0;
+                while let Some(k) = find(&s, &sep, start) {
+                    parts.push(from_units(&s[start..k]));
+                    start = k + sep.len();
+                }
+                parts.push(from_units(&s[start..]));
+            }
+        }
+        parts.truncate(limit);
+        Ok(vm.ctx.new_Array(parts))
+    },
+
+    #[prop(name=substring, length=2)]
+    fn substring(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let start = clamp_index(arg_integer(vm, args, 0)?, s.len());
+        let end = if is_undefined(args, 1) {
+            s.len()
+        } else {
+            clamp_index(arg_integer(vm, args, 1)?, s.len())
+        };
+        Ok(from_units(&s[start.min(end)..start.max(end)]))
+    },
+
+    #[prop(name=substr, length=2)]
+    fn substr(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let start = relative_index(arg_integer(vm, args, 0)?, s.len());
+        let len = if is_undefined(args, 1) {
+            f64::INFINITY
+        } else {
+            arg_integer(vm, args, 1)?
+        };
+        let end = clamp_index(start as f64 + len.max(0.), s.len());
+        Ok(from_units(&s[start..end.max(start)]))
+    },
+
+    #[prop(name=slice, length=2)]
+    fn slice(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let start = relative_index(arg_integer(vm, args, 0)?, s.len());
+        let end = if is_undefined(args, 1) {
+            s.len()
+        } else {
+            relative_index(arg_integer(vm, args, 1)?, s.len())
+        };
+        Ok(from_units(&s[start..end.max(start)]))
+    },
+
+    #[prop(name=concat, length=1)]
+    fn concat(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let mut s = this_units(vm, this)?;
+        for idx in 0..args.len() {
+            s.extend(arg_units(vm, args, idx)?);
         }
+        Ok(from_units(&s))
+    },
+
+    #[prop(name=trim, length=0)]
+    fn trim(vm: &mut VM, this: &Value, _args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let start = s.iter().position(|c| !is_whitespace(*c)).unwrap_or(s.len());
+        let end = s.iter().rposition(|c| !is_whitespace(*c)).map_or(start, |k| k + 1);
+        Ok(from_units(&s[start..end]))
+    },
+
+    #[prop(name=replace, length=2)]
+    fn replace(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let replacer = match args.get(1) {
+            Some(Value::Object(f)) if matches!(f.borrow().payload, ObjectPayload::Function(_)) => {
+                Some(f.clone())
+            }
+            _ => None,
+        };
+        let replacement = match replacer {
+            Some(_) => Units::new(),
+            None => arg_units(vm, args, 1)?,
+        };
+        let pos = match find(&s, &search, 0) {
+            Some(pos) => pos,
+            None => return Ok(from_units(&s)),
+        };
+        let matched = &s[pos..pos + search.len()];
+        let replaced = match replacer {
+            Some(f) => {
+                let args = [from_units(matched), (pos as f64).into(), from_units(&s)];
+                let res = f.Call(vm, Value::Undefined, &args)?;
+                units(res.ToString(vm)?.unwrap_string())
+            }
+            None => expand_replacement(&replacement, &s, pos, matched),
+        };
+        Ok(from_units(&[&s[..pos], &replaced, &s[pos + search.len()..]].concat()))
+    },
+
+    #[prop(name=startsWith, length=1)]
+    fn startsWith(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let start = clamp_index(arg_integer(vm, args, 1)?, s.len());
+        Ok(s[start..].starts_with(&search).into())
+    },
+
+    #[prop(name=endsWith, length=1)]
+    fn endsWith(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let end = if is_undefined(args, 1) {
+            s.len()
+        } else {
+            clamp_index(arg_integer(vm, args, 1)?, s.len())
+        };
+        Ok(s[..end].ends_with(&search).into())
+    },
+
+    #[prop(name=includes, length=1)]
+    fn includes(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let search = arg_units(vm, args, 0)?;
+        let start = clamp_index(arg_integer(vm, args, 1)?, s.len());
+        Ok(find(&s, &search, start).is_some().into())
+    },
+
+    #[prop(name=repeat, length=1)]
+    fn repeat(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        let s = this_units(vm, this)?;
+        let count = arg_integer(vm, args, 0)?;
+        if count < 0. || count.is_infinite() {
+            return Err(vm.ctx.new_Error(ErrorKind::RangeError, "Invalid count value"));
+        }
+        if s.is_empty() {
+            return Ok(from_units(&s));
+        }
+        if count > (MAX_STRING_LENGTH / s.len()) as f64 {
+            return Err(invalid_length(vm));
+        }
+        Ok(from_units(&s.repeat(count as usize)))
+    },
+
+    #[prop(name=padStart, length=1)]
+    fn padStart(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        pad(vm, this, args, true)
+    },
+
+    #[prop(name=padEnd, length=1)]
+    fn padEnd(vm: &mut VM, this: &Value, args: &[Value]) -> JSResult {
+        pad(vm, this, args, false)
     }
 }
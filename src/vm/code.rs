@@ -1,30 +1,114 @@
+use crate::vm::jsstring::JSString;
 use crate::vm::value::Value;
+use gc::{custom_trace, Finalize, Trace};
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub enum Instruction {
     LoadUndefined,
     LoadNull,
     LoadBool(bool),
-    PrintTop,
     BinAdd,
     BinSub,
     BinEq,
+    BinInstanceOf,
+    BinIn,
+    // Convert to a string, for a substitution of a template literal
+    ToString,
     LoadConst(usize),
+    // Call with an undefined this: [function, args..] -> [result]
     Call(usize),
+    // Call with an explicit this: [this, function, args..] -> [result]
+    CallWithThis(usize),
+    // The calls above, with an array of arguments to spread in place of them
+    CallSpread,
+    CallWithThisSpread,
     LoadName(usize),
     StoreName(usize),
     LoadArg(usize),
+    StoreArg(usize),
+    LoadArguments,
+    // [constructor, args..] -> [object]
     New(usize),
+    NewSpread,
     LoadProperty,
     StoreProperty,
     LoadThis,
     Return,
-    Throw,
-    PushThis,
-    PopThis,
     PopJumpIfFalse(usize),
+    // Jump unless the popped value is undefined, past a parameter default
+    PopJumpIfDefined(usize),
     Jump(usize),
+    Pop,
+    Dup,
+    // Match a switch case: [discriminant, test] -> [discriminant], or jump
+    // with [] when the two are strictly equal
+    JumpIfCase(usize),
+    // Pop a switch discriminant and jump through a table of Code::tables
+    JumpTable(usize),
     MakeArray(usize),
+    // [iterable] -> [array of its elements]
+    ToArray,
+    // [array] -> [array, array of its elements from an index on]
+    ArrayRest(usize),
+    // Append to an array: [array, value] -> [array]
+    ArrayPush,
+    // Append the elements of an iterable to an array: [array, iterable] ->
+    // [array]
+    ArraySpread,
+    MakeObject,
+    // Throw a TypeError for a null or undefined value to destructure
+    CheckObjectCoercible,
+    // Load a property of an object destructured with a rest property, noting
+    // its key: [object, keys, key] -> [object, keys, value]
+    LoadDestructured,
+    // [object, keys] -> [copy of the object's own enumerable properties
+    // besides the keys]
+    ObjectRest,
+    // [object] -> [array of the enumerable property names of a for-in loop]
+    ForInKeys,
+    // Step through an array: [array, index] -> [array, index + 1, element],
+    // or jump once past its end
+    IterNext(usize),
+    // Define a property of an object literal: [object, key, value] -> [object]
+    InitProperty,
+    InitGetter,
+    InitSetter,
+    // Push the object environment record of a with statement
+    EnterWith,
+    // Push the let and const bindings of a block of Code::blocks, uninitialized
+    PushScope(usize),
+    // Push the var bindings of Code::vars, once the parameters are bound
+    PushVarScope,
+    // Replace the innermost scope with a copy, for a new loop iteration
+    CopyScope,
+    PopScope,
+    // Initialize a let or const binding of the innermost scope declaring it
+    InitBinding(usize),
+    // Create a function from a template of Code::consts that closes over the
+    // current scope chain
+    MakeClosure(usize),
+    // Create an arrow function like MakeClosure, binding the this value, and
+    // the arguments object if asked, of this code in a scope of its own
+    MakeArrow(usize, bool),
+    // Push the this value of a call through a name: the object of the with
+    // statement binding it, if any, else undefined
+    ImplicitThis(usize),
+    // Create a class from the template of its constructor, extending the
+    // popped superclass if asked: [superclass?] -> [constructor, prototype]
+    MakeClass(usize, bool),
+    // Define a method of a class, on its prototype or, if static, on its
+    // constructor: [constructor, prototype, key, function] -> [constructor,
+    // prototype]
+    ClassMethod(bool),
+    ClassGetter(bool),
+    ClassSetter(bool),
+    // Construct this with the superclass constructor: [args..] -> [this]
+    SuperCall(usize),
+    SuperCallSpread,
+    // Load a property of the prototype of the method's home object: [this,
+    // key] -> [value]
+    LoadSuperProperty,
 }
 
 #[derive(Debug)]
@@ -32,6 +116,35 @@ pub struct Code {
     pub instrs: Vec<Instruction>,
     pub consts: Vec<Value>,
     pub names: Vec<String>,
+    pub tables: Vec<JumpTable>,
+    // The let and const names of each block, and whether they are mutable
+    pub blocks: Vec<Vec<(String, bool)>>,
+    // Parameter names by index, for names resolved at run time
+    pub params: Vec<String>,
+    // Set by a "use strict" directive, in this code or an enclosing one
+    pub strict: bool,
+    // Arrow functions take this and arguments from the scope chain
+    pub arrow: bool,
+    // Methods of classes, which like arrow functions aren't constructors
+    pub method: bool,
+    // Without defaults or a rest parameter, the arguments object is mapped
+    // to the parameters
+    pub simple_params: bool,
+    // The index of the rest parameter, if any
+    pub rest: Option<usize>,
+    // The var and function names of the code: for a function bound in a scope
+    // of their own inside that of the parameters, for a script properties of
+    // the global object
+    pub vars: Vec<String>,
+    // Class constructors can't be called without new, and those of derived
+    // classes bind this with super()
+    pub class_constructor: bool,
+    pub derived: bool,
+}
+
+impl Finalize for Code {}
+unsafe impl Trace for Code {
+    custom_trace!(this, mark(&this.consts));
 }
 
 impl Code {
@@ -40,6 +153,44 @@ impl Code {
             instrs,
             consts,
             names,
+            tables: Vec::new(),
+            blocks: Vec::new(),
+            params: Vec::new(),
+            strict: false,
+            arrow: false,
+            method: false,
+            simple_params: true,
+            rest: None,
+            vars: Vec::new(),
+            class_constructor: false,
+            derived: false,
         }
     }
 }
+
+// Targets of a switch whose cases are all small integer or string constants
+#[derive(Debug, Default)]
+pub struct JumpTable {
+    pub numbers: HashMap<i32, usize>,
+    pub strings: HashMap<JSString, usize>,
+    pub default: usize,
+}
+
+impl JumpTable {
+    // The integer key of a number, when it is one
+    pub fn number_key(n: f64) -> Option<i32> {
+        if n.fract() == 0.0 && n >= i32::MIN as f64 && n <= i32::MAX as f64 {
+            Some(n as i32)
+        } else {
+            None
+        }
+    }
+    pub fn target(&self, value: &Value) -> usize {
+        let target = match value {
+            Value::Number(n) => Self::number_key(*n).and_then(|n| self.numbers.get(&n)),
+            Value::String(s) => self.strings.get(s),
+            _ => None,
+        };
+        target.cloned().unwrap_or(self.default)
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(non_snake_case)]
 
 use crate::objects::*;
+use crate::vm::scope::GcEnv;
 
 use gc::{Gc, GcCell};
 
@@ -9,10 +10,19 @@ pub struct Context {
     pub Object_function: GcObject,
     pub Function_prototype: GcObject,
     pub Function_function: GcObject,
+    pub Array_prototype: GcObject,
+    pub Array_function: GcObject,
+    pub Boolean_prototype: GcObject,
+    pub Boolean_function: GcObject,
+    pub Date_prototype: GcObject,
+    pub Date_function: GcObject,
+    pub Error_prototypes: [GcObject; 7],
+    pub Error_functions: [GcObject; 7],
     pub Number_prototype: GcObject,
     pub Number_function: GcObject,
     pub String_prototype: GcObject,
     pub String_function: GcObject,
+    pub Math: GcObject,
 }
 
 impl Context {
@@ -22,25 +32,63 @@ impl Context {
         let Function_function = Self::build_Function_function(Function_prototype.clone());
         let Object_function =
             Self::build_Object_function(Function_prototype.clone(), Object_prototype.clone());
+        let Array_prototype = Self::build_Array_prototype(Object_prototype.clone());
+        let Array_function =
+            Self::build_Array_function(Function_prototype.clone(), Array_prototype.clone());
+        let Boolean_prototype = Self::build_Boolean_prototype(Object_prototype.clone());
+        let Boolean_function =
+            Self::build_Boolean_function(Function_prototype.clone(), Boolean_prototype.clone());
+        let Date_prototype = Self::build_Date_prototype(Object_prototype.clone());
+        let Date_function =
+            Self::build_Date_function(Function_prototype.clone(), Date_prototype.clone());
+        let Error_prototypes = Self::build_Error_prototypes(Object_prototype.clone());
+        let Error_functions =
+            Self::build_Error_functions(Function_prototype.clone(), &Error_prototypes);
         let Number_prototype = Self::build_Number_prototype(Object_prototype.clone());
         let Number_function =
             Self::build_Number_function(Function_prototype.clone(), Number_prototype.clone());
         let String_prototype = Self::build_String_prototype(Object_prototype.clone());
         let String_function =
             Self::build_String_function(Function_prototype.clone(), String_prototype.clone());
+        let Math = Self::build_Math(Object_prototype.clone());
         let mut ctx = Context {
             Object_prototype,
             Object_function,
             Function_prototype,
             Function_function,
+            Array_prototype,
+            Array_function,
+            Boolean_prototype,
+            Boolean_function,
+            Date_prototype,
+            Date_function,
+            Error_prototypes,
+            Error_functions,
             Number_prototype,
             Number_function,
             String_prototype,
             String_function,
+            Math,
         };
+        let Object_props = object::get_function_props(&ctx);
+        ctx.Object_function.borrow_mut().props.extend(Object_props);
+        ctx.Array_prototype.borrow_mut().props = array::get_prototype_props(&ctx);
+        ctx.Boolean_prototype.borrow_mut().props = boolean::get_prototype_props(&ctx);
+        ctx.Date_prototype.borrow_mut().props = date::get_prototype_props(&ctx);
+        let Date_props = date::get_function_props(&ctx);
+        ctx.Date_function.borrow_mut().props.extend(Date_props);
+        for kind in error::ErrorKind::ALL.iter() {
+            ctx.Error_prototype(*kind).borrow_mut().props = error::get_prototype_props(&ctx, *kind);
+        }
         ctx.Number_prototype.borrow_mut().props = number::get_prototype_props(&ctx);
+        let Number_props = number::get_function_props(&ctx);
+        ctx.Number_function.borrow_mut().props.extend(Number_props);
+        ctx.Function_prototype.borrow_mut().props = function::get_prototype_props(&ctx);
         ctx.init_Object_prototype();
         ctx.String_prototype.borrow_mut().props = string::get_prototype_props(&ctx);
+        let String_props = string::get_function_props(&ctx);
+        ctx.String_function.borrow_mut().props.extend(String_props);
+        ctx.Math.borrow_mut().props = math::get_props(&ctx);
         ctx
     }
 
@@ -51,6 +99,7 @@ impl Context {
             __proto__: None,
             payload: ObjectPayload::Regular(object::Regular),
             props: JSDict::new(),
+            extensible: true,
         }))
     }
     // Function.prototype
@@ -58,24 +107,22 @@ impl Context {
         let Function_prototype = Object {
             __proto__: Some(Object_prototype),
             props: JSDict::new(),
+            extensible: true,
             payload: ObjectPayload::Regular(object::Regular),
         };
         Gc::new(GcCell::new(Function_prototype))
     }
     // function Function ()
     fn build_Function_function(Function_prototype: GcObject) -> GcObject {
-        let Function_object = Object {
-            __proto__: Some(Function_prototype.clone()),
-            props: JSDict::new(),
-            payload: ObjectPayload::Function(function::Function::new_primitive(
-                "Function".into(),
-                function::function,
-                function::constructor,
-                0,
-                Function_prototype,
-            )),
-        };
-        Gc::new(GcCell::new(Function_object))
+        Self::new_PrimitiveFunction(
+            Function_prototype.clone(),
+            Function_prototype,
+            "Function",
+            function::function,
+            function::constructor,
+            1,
+        )
+        .unwrap_object()
     }
     // function Object ()
     fn build_Object_function(Function_prototype: GcObject, Object_prototype: GcObject) -> GcObject {
@@ -85,23 +132,129 @@ impl Context {
                 "Object".into(),
                 object::function,
                 object::constructor,
-                0,
+                1,
                 Object_prototype.clone(),
             )),
-            props: JSDict::new(),
+            props: function_props("Object", 1),
+            extensible: true,
         };
-        Object.props.insert(
-            "prototype".to_string(),
-            Property::new(Object_prototype.into()),
-        );
+        Object
+            .props
+            .insert("prototype".into(), Property::new(Object_prototype.into()));
         Gc::new(GcCell::new(Object))
     }
 
+    fn build_Array_prototype(Object_prototype: GcObject) -> GcObject {
+        let Array_prototype = Object {
+            __proto__: Some(Object_prototype),
+            payload: ObjectPayload::Regular(object::Regular),
+            props: JSDict::new(),
+            extensible: true,
+        };
+        Gc::new(GcCell::new(Array_prototype))
+    }
+
+    fn build_Array_function(Function_prototype: GcObject, Array_prototype: GcObject) -> GcObject {
+        Self::new_PrimitiveFunction(
+            Function_prototype,
+            Array_prototype,
+            "Array",
+            array::function,
+            array::constructor,
+            1,
+        )
+        .unwrap_object()
+    }
+
+    fn build_Boolean_prototype(Object_prototype: GcObject) -> GcObject {
+        let Boolean_prototype = Object {
+            __proto__: Some(Object_prototype),
+            payload: ObjectPayload::Regular(object::Regular),
+            props: JSDict::new(),
+            extensible: true,
+        };
+        Gc::new(GcCell::new(Boolean_prototype))
+    }
+
+    fn build_Boolean_function(
+        Function_prototype: GcObject,
+        Boolean_prototype: GcObject,
+    ) -> GcObject {
+        Self::new_PrimitiveFunction(
+            Function_prototype,
+            Boolean_prototype,
+            "Boolean",
+            boolean::function,
+            boolean::constructor,
+            1,
+        )
+        .unwrap_object()
+    }
+
+    fn build_Date_prototype(Object_prototype: GcObject) -> GcObject {
+        let Date_prototype = Object {
+            __proto__: Some(Object_prototype),
+            payload: ObjectPayload::Regular(object::Regular),
+            props: JSDict::new(),
+            extensible: true,
+        };
+        Gc::new(GcCell::new(Date_prototype))
+    }
+
+    fn build_Date_function(Function_prototype: GcObject, Date_prototype: GcObject) -> GcObject {
+        Self::new_PrimitiveFunction(
+            Function_prototype,
+            Date_prototype,
+            "Date",
+            date::function,
+            date::constructor,
+            7,
+        )
+        .unwrap_object()
+    }
+
+    // Error.prototype, and the native error prototypes inheriting from it
+    fn build_Error_prototypes(Object_prototype: GcObject) -> [GcObject; 7] {
+        let Error_prototype = Gc::new(GcCell::new(Object {
+            __proto__: Some(Object_prototype),
+            payload: ObjectPayload::Error(error::Error),
+            props: JSDict::new(),
+            extensible: true,
+        }));
+        error::ErrorKind::ALL.map(|kind| match kind {
+            error::ErrorKind::Error => Error_prototype.clone(),
+            _ => Gc::new(GcCell::new(Object {
+                __proto__: Some(Error_prototype.clone()),
+                payload: ObjectPayload::Error(error::Error),
+                props: JSDict::new(),
+                extensible: true,
+            })),
+        })
+    }
+
+    fn build_Error_functions(
+        Function_prototype: GcObject,
+        Error_prototypes: &[GcObject; 7],
+    ) -> [GcObject; 7] {
+        error::ErrorKind::ALL.map(|kind| {
+            Self::new_PrimitiveFunction(
+                Function_prototype.clone(),
+                Error_prototypes[kind as usize].clone(),
+                kind.name(),
+                kind.function(),
+                kind.function(),
+                1,
+            )
+            .unwrap_object()
+        })
+    }
+
     fn build_Number_prototype(Object_prototype: GcObject) -> GcObject {
         let Number_prototype = Object {
             __proto__: Some(Object_prototype),
             payload: ObjectPayload::Regular(object::Regular),
             props: JSDict::new(),
+            extensible: true,
         };
         Gc::new(GcCell::new(Number_prototype))
     }
@@ -113,7 +266,7 @@ impl Context {
             "Number",
             number::function,
             number::constructor,
-            0,
+            1,
         )
         .unwrap_object()
     }
@@ -122,51 +275,174 @@ impl Context {
             __proto__: Some(Object_prototype),
             payload: ObjectPayload::Regular(object::Regular),
             props: JSDict::new(),
+            extensible: true,
         };
         Gc::new(GcCell::new(String_prototype))
     }
 
     fn build_String_function(Function_prototype: GcObject, String_prototype: GcObject) -> GcObject {
-        let String_function = Object {
-            __proto__: Some(Function_prototype),
-            payload: ObjectPayload::Function(function::Function::new_primitive(
-                "Number".into(),
-                string::function,
-                string::constructor,
-                0,
-                String_prototype,
-            )),
+        Self::new_PrimitiveFunction(
+            Function_prototype,
+            String_prototype,
+            "String",
+            string::function,
+            string::constructor,
+            1,
+        )
+        .unwrap_object()
+    }
+
+    // Math
+    fn build_Math(Object_prototype: GcObject) -> GcObject {
+        let Math = Object {
+            __proto__: Some(Object_prototype),
+            payload: ObjectPayload::Math(math::Math),
             props: JSDict::new(),
+            extensible: true,
         };
-        Gc::new(GcCell::new(String_function))
+        Gc::new(GcCell::new(Math))
     }
 }
 
 impl Context {
     fn init_Object_prototype(&mut self) {
-        fn insert_prop(prototype: &mut GcObject, key: String, value: Value) {
-            prototype
+        let functions: [(&'static str, RJSFunc, usize); 6] = [
+            ("valueOf", object::valueOf, 0),
+            ("toString", object::toString, 0),
+            ("toLocaleString", object::toLocaleString, 0),
+            ("hasOwnProperty", object::hasOwnProperty, 1),
+            ("isPrototypeOf", object::isPrototypeOf, 1),
+            ("propertyIsEnumerable", object::propertyIsEnumerable, 1),
+        ];
+        for (name, func, length) in functions.iter() {
+            let func = self.new_BuiltinFunction(name, *func, *length);
+            self.Object_prototype
                 .borrow_mut()
                 .props
-                .insert(key, Property::new(value));
+                .insert((*name).into(), Property::new(func).dont_enum());
         }
-        let valueOf = self.new_BuiltinFunction("valueOf", object::valueOf, 0);
-        let toString = self.new_BuiltinFunction("toString", object::toString, 0);
-        insert_prop(&mut self.Object_prototype, "valueOf".to_string(), valueOf);
-        insert_prop(&mut self.Object_prototype, "toString".to_string(), toString);
     }
 }
 
 use super::code::Code;
+use super::jsstring::JSString;
 use super::value::Value;
 use std::rc::Rc;
 
+// The `length` and `name` own properties of every function object
+fn function_props(name: &str, length: usize) -> JSDict {
+    let mut props = JSDict::new();
+    props.insert(
+        "length".into(),
+        Property::new((length as f64).into())
+            .read_only()
+            .dont_enum()
+            .dont_delete(),
+    );
+    props.insert(
+        "name".into(),
+        Property::new(name.into()).read_only().dont_enum(),
+    );
+    props
+}
+
 impl Context {
+    pub fn new_Array(&self, elements: Vec<Value>) -> Value {
+        let mut props = JSDict::new();
+        props.insert(
+            "length".into(),
+            Property::new((elements.len() as f64).into())
+                .dont_enum()
+                .dont_delete(),
+        );
+        for (idx, el) in elements.into_iter().enumerate() {
+            props.insert(idx.to_string().into(), Property::new(el));
+        }
+        let a = Object {
+            __proto__: Some(self.Array_prototype.clone()),
+            payload: ObjectPayload::Array(array::Array),
+            props,
+            extensible: true,
+        };
+        Value::Object(Gc::new(GcCell::new(a)))
+    }
+    // The strings array passed to the tag of a tagged template, frozen, with
+    // the raw strings as its raw property (ES6 12.2.9.3)
+    pub fn new_TemplateObject(&self, cooked: Vec<Value>, raw: Vec<Value>) -> Value {
+        let raw = self.new_Array(raw);
+        raw.unwrap_object().borrow_mut().freeze();
+        let template = self.new_Array(cooked);
+        {
+            let template = template.unwrap_object();
+            let mut template = template.borrow_mut();
+            template
+                .props
+                .insert("raw".into(), Property::new(raw).dont_enum());
+            template.freeze();
+        }
+        template
+    }
+    pub fn new_Arguments(&self, args: Vec<Value>, callee: GcObject) -> Value {
+        let mut props = JSDict::new();
+        props.insert(
+            "length".into(),
+            Property::new((args.len() as f64).into()).dont_enum(),
+        );
+        props.insert("callee".into(), Property::new(callee.into()).dont_enum());
+        for (idx, arg) in args.into_iter().enumerate() {
+            props.insert(idx.to_string().into(), Property::new(arg));
+        }
+        let a = Object {
+            __proto__: Some(self.Object_prototype.clone()),
+            payload: ObjectPayload::Arguments(function::Arguments),
+            props,
+            extensible: true,
+        };
+        Value::Object(Gc::new(GcCell::new(a)))
+    }
+    pub fn new_Boolean(&self, value: bool) -> Value {
+        let b = Object {
+            __proto__: Some(self.Boolean_prototype.clone()),
+            payload: ObjectPayload::Boolean(boolean::Boolean::new(value)),
+            props: JSDict::new(),
+            extensible: true,
+        };
+        Value::Object(Gc::new(GcCell::new(b)))
+    }
+    pub fn new_Date(&self, value: f64) -> Value {
+        let d = Object {
+            __proto__: Some(self.Date_prototype.clone()),
+            payload: ObjectPayload::Date(date::Date::new(value)),
+            props: JSDict::new(),
+            extensible: true,
+        };
+        Value::Object(Gc::new(GcCell::new(d)))
+    }
+    pub fn Error_prototype(&self, kind: error::ErrorKind) -> &GcObject {
+        &self.Error_prototypes[kind as usize]
+    }
+    pub fn Error_function(&self, kind: error::ErrorKind) -> &GcObject {
+        &self.Error_functions[kind as usize]
+    }
+    pub fn new_Error(&self, kind: error::ErrorKind, message: &str) -> Value {
+        let mut props = JSDict::new();
+        if !message.is_empty() {
+            props.insert("message".into(), Property::new(message.into()).dont_enum());
+        }
+        let e = Object {
+            __proto__: Some(self.Error_prototype(kind).clone()),
+            payload: ObjectPayload::Error(error::Error),
+            props,
+            extensible: true,
+        };
+        Value::Object(Gc::new(GcCell::new(e)))
+    }
     pub fn new_Number(&self, value: f64) -> Value {
         let n = Object {
             __proto__: Some(self.Number_prototype.clone()),
             payload: ObjectPayload::Number(number::Number::new(value)),
             props: JSDict::new(),
+            extensible: true,
         };
         Value::Object(Gc::new(GcCell::new(n)))
     }
@@ -175,31 +451,64 @@ impl Context {
             __proto__,
             payload: ObjectPayload::Regular(object::Regular),
             props: JSDict::new(),
+            extensible: true,
         };
         Value::Object(Gc::new(GcCell::new(object)))
     }
-    pub fn new_Function(&self, name: String, code: Rc<Code>, length: usize) -> Value {
+    pub fn new_Function(
+        &self,
+        name: String,
+        code: Rc<Code>,
+        length: usize,
+        source: JSString,
+    ) -> Value {
         let prototype = self
             .new_Object(self.Object_prototype.clone().into())
             .unwrap_object();
         let mut object = Object {
             __proto__: self.Function_prototype.clone().into(),
+            props: function_props(&name, length),
+            extensible: true,
             payload: ObjectPayload::Function(function::Function::new_userdefined(
                 code,
                 name,
                 length,
                 prototype.clone(),
+                source,
             )),
-            props: JSDict::new(),
         };
         fn init_Function_object(object: &mut Object, prototype: GcObject) {
             object
                 .props
-                .insert("prototype".to_string(), Property::new(prototype.into()));
+                .insert("prototype".into(), Property::new(prototype.into()));
         }
         init_Function_object(&mut object, prototype);
         Value::Object(Gc::new(GcCell::new(object)))
     }
+    // A new function object for an evaluation of a function expression or
+    // declaration, closing over the scope chain it is evaluated in
+    pub fn new_Closure(&self, template: &GcObject, env: Option<GcEnv>) -> Value {
+        let function = match template.borrow().payload {
+            ObjectPayload::Function(ref f) => f.clone(),
+            _ => panic!("Not a function template"),
+        };
+        let prototype = self
+            .new_Object(self.Object_prototype.clone().into())
+            .unwrap_object();
+        let mut object = Object {
+            __proto__: self.Function_prototype.clone().into(),
+            props: template.borrow().props.clone(),
+            extensible: true,
+            payload: ObjectPayload::Function(function.closure(template, prototype.clone(), env)),
+        };
+        // Arrow functions have no prototype
+        if object.props.contains_key(&JSString::from("prototype")) {
+            object
+                .props
+                .insert("prototype".into(), Property::new(prototype.into()));
+        }
+        object.into()
+    }
     pub fn new_PrimitiveFunction(
         Function_prototype: GcObject,
         prototype: GcObject,
@@ -220,11 +529,12 @@ impl Context {
                 length,
                 prototype.clone(),
             )),
-            props: JSDict::new(),
+            props: function_props(name, length),
+            extensible: true,
         };
         object
             .props
-            .insert("prototype".to_string(), Property::new(prototype.into()));
+            .insert("prototype".into(), Property::new(prototype.into()));
         object.into()
     }
 
@@ -236,16 +546,45 @@ impl Context {
                 func,
                 length,
             )),
-            props: JSDict::new(),
+            props: function_props(name, length),
+            extensible: true,
+        };
+        object.into()
+    }
+
+    pub fn new_BoundFunction(
+        &self,
+        target: GcObject,
+        this: Value,
+        args: Vec<Value>,
+        name: String,
+        length: usize,
+    ) -> Value {
+        let object = Object {
+            __proto__: self.Function_prototype.clone().into(),
+            props: function_props(&name, length),
+            extensible: true,
+            payload: ObjectPayload::Function(function::Function::new_bound(
+                name, length, target, this, args,
+            )),
         };
         object.into()
     }
 
-    pub fn new_String(&self, value: String) -> Value {
+    pub fn new_String(&self, value: JSString) -> Value {
+        let mut props = JSDict::new();
+        props.insert(
+            "length".into(),
+            Property::new((value.len() as f64).into())
+                .read_only()
+                .dont_enum()
+                .dont_delete(),
+        );
         let object = Object {
             __proto__: self.String_prototype.clone().into(),
             payload: ObjectPayload::String(string::String::new(value)),
-            props: JSDict::new(),
+            props,
+            extensible: true,
         };
         object.into()
     }
@@ -0,0 +1,86 @@
+use gc::{unsafe_empty_trace, Finalize, Trace};
+
+use std::rc::Rc;
+
+/// A JavaScript string: an immutable sequence of UTF-16 code units.
+///
+/// Unlike a Rust `String` it can hold lone surrogates, and `len` and indexing
+/// count code units as the language does.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JSString(Rc<[u16]>);
+
+impl JSString {
+    pub fn units(&self) -> &[u16] {
+        &self.0
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn concat(&self, other: &JSString) -> JSString {
+        [self.units(), other.units()].concat().into()
+    }
+    /// Exact conversion for the host, fails on lone surrogates
+    pub fn to_rust_string(&self) -> Result<String, std::string::FromUtf16Error> {
+        String::from_utf16(&self.0)
+    }
+    /// Replaces lone surrogates with U+FFFD
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf16_lossy(&self.0)
+    }
+}
+
+impl Finalize for JSString {}
+unsafe impl Trace for JSString {
+    unsafe_empty_trace!();
+}
+
+impl Default for JSString {
+    fn default() -> Self {
+        JSString(Rc::new([]))
+    }
+}
+
+impl From<&str> for JSString {
+    fn from(s: &str) -> Self {
+        JSString(s.encode_utf16().collect::<Vec<u16>>().into())
+    }
+}
+
+impl From<String> for JSString {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<&[u16]> for JSString {
+    fn from(units: &[u16]) -> Self {
+        JSString(units.into())
+    }
+}
+
+impl From<Vec<u16>> for JSString {
+    fn from(units: Vec<u16>) -> Self {
+        JSString(units.into())
+    }
+}
+
+impl PartialEq<&str> for JSString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0.iter().copied().eq(other.encode_utf16())
+    }
+}
+
+impl std::fmt::Display for JSString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+impl std::fmt::Debug for JSString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_string_lossy())
+    }
+}
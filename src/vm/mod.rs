@@ -1,4 +1,8 @@
 pub mod code;
 pub mod context;
+pub mod jsstring;
+pub mod options;
+pub mod random;
+pub mod scope;
 pub mod value;
 pub mod vm;
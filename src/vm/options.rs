@@ -0,0 +1,46 @@
+use super::random::Random;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current time in milliseconds since 01 January, 1970 UTC
+pub type Clock = Box<dyn Fn() -> f64>;
+/// Offset of local time from UTC in milliseconds (DST included) at the given UTC time
+pub type TimeZone = Box<dyn Fn(f64) -> f64>;
+/// Receives each line written by `print`, without its line break
+pub type Output = Box<dyn FnMut(&str)>;
+
+/// Host hooks supplied by the embedder when building a `VM`
+pub struct Options {
+    pub random: Random,
+    pub clock: Clock,
+    pub timezone: TimeZone,
+    pub output: Output,
+}
+
+pub fn system_clock() -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_millis() as f64,
+        Err(e) => -(e.duration().as_millis() as f64),
+    }
+}
+
+impl Options {
+    pub fn with_seed(seed: u64) -> Self {
+        Options {
+            random: Random::from_seed(seed),
+            ..Options::default()
+        }
+    }
+}
+
+impl Default for Options {
+    // std has no access to the host's zone database, embedders that care supply one
+    fn default() -> Self {
+        Options {
+            random: Random::new(),
+            clock: Box::new(system_clock),
+            timezone: Box::new(|_| 0.),
+            output: Box::new(|line| println!("{}", line)),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+pub type EntropyFn = Box<dyn FnMut() -> u64>;
+
+enum Source {
+    XorShift128Plus([u64; 2]),
+    Entropy(EntropyFn),
+}
+
+/// Source of randomness behind `Math.random`
+pub struct Random {
+    source: Source,
+}
+
+// Spreads a 64-bit seed over the generator state, as recommended for xorshift
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Random {
+    /// Seeds the built-in xorshift128+ generator from the host
+    pub fn new() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0);
+        Self::from_seed(hasher.finish())
+    }
+    /// Same seed, same sequence
+    pub fn from_seed(seed: u64) -> Self {
+        let mut seed = seed;
+        let mut state = [splitmix64(&mut seed), splitmix64(&mut seed)];
+        if state == [0, 0] {
+            state[0] = 1;
+        }
+        Random {
+            source: Source::XorShift128Plus(state),
+        }
+    }
+    /// Every call to `Math.random` draws 64 bits from `entropy`
+    #[allow(dead_code)] // embedder API, the CLI only seeds
+    pub fn from_entropy(entropy: EntropyFn) -> Self {
+        Random {
+            source: Source::Entropy(entropy),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match &mut self.source {
+            Source::XorShift128Plus(state) => {
+                let mut s1 = state[0];
+                let s0 = state[1];
+                let result = s0.wrapping_add(s1);
+                state[0] = s0;
+                s1 ^= s1 << 23;
+                state[1] = s1 ^ s0 ^ (s1 >> 17) ^ (s0 >> 26);
+                result
+            }
+            Source::Entropy(entropy) => entropy(),
+        }
+    }
+
+    /// Uniformly distributed in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,143 @@
+use crate::objects::GcObject;
+use crate::vm::value::Value;
+use gc::{Finalize, Gc, GcCell, Trace};
+use std::collections::HashMap;
+
+pub type GcEnv = Gc<Environment>;
+
+/// An environment record on the scope chain (ES5 10.2.1): the let and const
+/// bindings of a block or the var bindings of a function call, the object of
+/// a with statement, or the parameters of a function call
+#[derive(Trace, Finalize, Debug)]
+pub enum Record {
+    Declarative(GcCell<HashMap<String, Binding>>),
+    Object(GcObject),
+    Parameters(GcCell<Parameters>),
+}
+
+#[derive(Trace, Finalize, Debug)]
+pub struct Environment {
+    pub record: Record,
+    pub parent: Option<GcEnv>,
+}
+
+#[derive(Trace, Finalize, Clone, Debug)]
+pub struct Parameters {
+    pub names: Vec<String>,
+    // By parameter index, undefined for those without an argument
+    pub values: Vec<Value>,
+    // The arguments as passed
+    pub args: Vec<Value>,
+    // Created on first use; parameters with a passed argument then live in it,
+    // unless the parameter list has defaults or a rest parameter (ES6 9.2.12)
+    pub arguments: Option<GcObject>,
+    pub mapped: bool,
+}
+
+#[derive(Trace, Finalize, Clone, Debug)]
+pub struct Binding {
+    // None until its declaration runs, the temporal dead zone
+    pub value: Option<Value>,
+    pub mutable: bool,
+}
+
+impl Environment {
+    // The uninitialized bindings of a block, given as names and whether they
+    // are mutable
+    pub fn declarative(names: &[(String, bool)], parent: Option<GcEnv>) -> GcEnv {
+        let bindings = names
+            .iter()
+            .map(|(name, mutable)| {
+                let binding = Binding {
+                    value: None,
+                    mutable: *mutable,
+                };
+                (name.clone(), binding)
+            })
+            .collect();
+        Gc::new(Environment {
+            record: Record::Declarative(GcCell::new(bindings)),
+            parent,
+        })
+    }
+    pub fn parameters(
+        names: Vec<String>,
+        args: &[Value],
+        mapped: bool,
+        parent: Option<GcEnv>,
+    ) -> GcEnv {
+        let mut values = args[..args.len().min(names.len())].to_vec();
+        values.resize(names.len(), Value::Undefined);
+        let params = Parameters {
+            names,
+            values,
+            args: args.to_vec(),
+            arguments: None,
+            mapped,
+        };
+        Gc::new(Environment {
+            record: Record::Parameters(GcCell::new(params)),
+            parent,
+        })
+    }
+    // The var bindings of a function call, given as names and initial values
+    pub fn variables(vars: Vec<(String, Value)>, parent: Option<GcEnv>) -> GcEnv {
+        let bindings = vars
+            .into_iter()
+            .map(|(name, value)| {
+                let binding = Binding {
+                    value: Some(value),
+                    mutable: true,
+                };
+                (name, binding)
+            })
+            .collect();
+        Gc::new(Environment {
+            record: Record::Declarative(GcCell::new(bindings)),
+            parent,
+        })
+    }
+    pub fn object(object: GcObject, parent: Option<GcEnv>) -> GcEnv {
+        Gc::new(Environment {
+            record: Record::Object(object),
+            parent,
+        })
+    }
+    // A record with the same bindings and their current values, for the next
+    // iteration of a loop (ES6 13.6.3.3)
+    pub fn copy(&self) -> GcEnv {
+        let record = match self.record {
+            Record::Declarative(ref bindings) => {
+                Record::Declarative(GcCell::new(bindings.borrow().clone()))
+            }
+            Record::Object(ref object) => Record::Object(object.clone()),
+            Record::Parameters(ref params) => {
+                Record::Parameters(GcCell::new(params.borrow().clone()))
+            }
+        };
+        Gc::new(Environment {
+            record,
+            parent: self.parent.clone(),
+        })
+    }
+    pub fn binding(&self, name: &String) -> Option<Binding> {
+        match self.record {
+            Record::Declarative(ref bindings) => bindings.borrow().get(name).cloned(),
+            Record::Object(_) | Record::Parameters(_) => None,
+        }
+    }
+    pub fn params(&self) -> &GcCell<Parameters> {
+        match self.record {
+            Record::Parameters(ref params) => params,
+            _ => panic!("Not a parameter scope"),
+        }
+    }
+    // Sets a binding without checking it is initialized or mutable
+    pub fn set_binding(&self, name: &String, value: Value) {
+        if let Record::Declarative(ref bindings) = self.record {
+            if let Some(binding) = bindings.borrow_mut().get_mut(name) {
+                binding.value = Some(value);
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::objects::*;
 use crate::vm::context::Context;
+use crate::vm::jsstring::JSString;
 use crate::vm::vm::VM;
 use gc::{Finalize, Gc, GcCell, Trace};
 
@@ -11,12 +12,12 @@ pub enum Value {
     Undefined,
     Number(f64),
     Boolean(bool),
-    String(String),
+    String(JSString),
     Object(GcObject),
 }
 
 impl Value {
-    fn ToBoolean(&self) -> Value {
+    pub fn ToBoolean(&self) -> Value {
         match self {
             Value::Undefined | Value::Null => Value::Boolean(false),
             Value::Boolean(_) => self.clone(),
@@ -25,7 +26,7 @@ impl Value {
                 n if n == &0. => Value::Boolean(false),
                 _ => Value::Boolean(true),
             },
-            Value::String(s) => Value::Boolean(s.len() > 0),
+            Value::String(s) => Value::Boolean(!s.is_empty()),
             Value::Object(_) => Value::Boolean(true),
         }
     }
@@ -35,12 +36,8 @@ impl Value {
             Value::Null => Value::Number(0.),
             Value::Number(_) => self.clone(),
             Value::Boolean(b) => Value::Number(if *b { 1. } else { 0. }),
-            // TODO: impl the spec
-            Value::String(s) => Value::Number(s.parse::<f64>().unwrap_or_default()),
-            Value::Object(o) => {
-                o.borrow()
-                    .DefaultValue(PreferredType::Number.into(), &self.unwrap_object(), vm)?
-            }
+            Value::String(s) => Value::Number(number::from_string(s)),
+            Value::Object(o) => o.DefaultValue(PreferredType::Number.into(), vm)?,
         })
     }
 
@@ -50,7 +47,7 @@ impl Value {
 
     pub fn ToPrimitive(&self, vm: &mut VM) -> JSResult {
         match self {
-            Value::Object(o) => o.borrow().DefaultValue(None, &self.unwrap_object(), vm),
+            Value::Object(o) => o.DefaultValue(None, vm),
             _ => Ok(self.clone()),
         }
     }
@@ -59,20 +56,18 @@ impl Value {
             Value::Undefined => Ok("undefined".into()),
             Value::Null => Ok("null".into()),
             Value::Boolean(b) => Ok(b.to_string().into()),
-            Value::Number(n) => Ok(n.to_string().into()),
+            Value::Number(_) => Ok(self.to_string().into()),
             Value::String(s) => Ok(s.clone().into()),
-            Value::Object(o) => {
-                vm.push_this(self.clone());
-                let ret = o.borrow().DefaultValue(
-                    PreferredType::String.into(),
-                    &self.unwrap_object(),
-                    vm,
-                );
-                vm.pop_this();
-                ret
-            }
+            Value::Object(o) => o
+                .DefaultValue(PreferredType::String.into(), vm)?
+                .ToString(vm),
         }
     }
+    // ToPropertyKey (ES6 7.1.14): the name a value stands for as a property
+    // key, lone surrogates included
+    pub fn ToPropertyKey(&self, vm: &mut VM) -> Result<JSString, Value> {
+        Ok(self.ToString(vm)?.unwrap_string().clone())
+    }
 }
 
 impl std::fmt::Display for Value {
@@ -83,12 +78,9 @@ impl std::fmt::Display for Value {
             match self {
                 Value::Null => String::from("Null"),
                 Value::Undefined => String::from("Undefined"),
-                Value::Number(n) => match n {
-                    n if n.is_nan() => String::from("NaN"),
-                    _ => n.to_string(),
-                },
+                Value::Number(n) => number::to_string(*n),
                 Value::Boolean(b) => b.to_string(),
-                Value::String(s) => s.clone(),
+                Value::String(s) => s.to_string_lossy(),
                 // TODO: impl Object A/C spec
                 Value::Object(_) => String::from("object"),
             }
@@ -102,11 +94,7 @@ impl std::ops::Add for Value {
     fn add(self, other: Self) -> Self {
         match (&self, &other) {
             (Value::Number(n1), Value::Number(n2)) => (n1 + n2).into(),
-            (Value::String(s1), Value::String(s2)) => {
-                let mut res = s1.clone();
-                res.push_str(s2.as_str());
-                res.into()
-            }
+            (Value::String(s1), Value::String(s2)) => s1.concat(s2).into(),
             _ => Value::Undefined,
         }
     }
@@ -131,7 +119,9 @@ impl std::cmp::PartialEq for Value {
         match (self, other) {
             (Value::Number(n1), Value::Number(n2)) => n1 == n2,
             (Value::Boolean(b1), Value::Boolean(b2)) => b1 == b2,
-            (Value::Object(_), Value::Object(_)) => false,
+            (Value::String(s1), Value::String(s2)) => s1 == s2,
+            (Value::Object(o1), Value::Object(o2)) => Gc::ptr_eq(o1, o2),
+            (Value::Undefined, Value::Undefined) | (Value::Null, Value::Null) => true,
             (Value::Undefined, Value::Null) => true,
             (Value::Null, Value::Undefined) => true,
             _ => false,
@@ -140,9 +130,28 @@ impl std::cmp::PartialEq for Value {
 }
 
 impl Value {
+    // The Strict Equality Comparison Algorithm (ES5 11.9.6)
+    pub fn StrictEquals(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Undefined, Value::Null) | (Value::Null, Value::Undefined) => false,
+            _ => self == other,
+        }
+    }
+    // SameValue (ES5 9.12): like ===, but NaN is itself and +0 isn't -0
+    pub fn SameValue(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Number(n1), Value::Number(n2)) => {
+                (n1.is_nan() && n2.is_nan())
+                    || (n1 == n2 && n1.is_sign_negative() == n2.is_sign_negative())
+            }
+            (Value::Undefined, Value::Null) | (Value::Null, Value::Undefined) => false,
+            _ => self == other,
+        }
+    }
     pub fn as_object(&self, ctx: &Context) -> GcObject {
         match self {
             Value::Object(o) => o.clone(),
+            Value::Boolean(b) => ctx.new_Boolean(*b).unwrap_object(),
             Value::Number(n) => ctx.new_Number(*n).unwrap_object(),
             Value::String(s) => ctx.new_String(s.clone()).unwrap_object(),
             _ => panic!("Not an object"),
@@ -154,7 +163,7 @@ impl Value {
             _ => panic!("Fatal Error: Not an object!"),
         }
     }
-    pub fn unwrap_string(&self) -> &String {
+    pub fn unwrap_string(&self) -> &JSString {
         match self {
             Value::String(o) => o,
             _ => panic!("Fatal Error: Not a String!"),
@@ -174,9 +183,15 @@ impl From<f64> for Value {
     }
 }
 
+impl From<JSString> for Value {
+    fn from(val: JSString) -> Self {
+        Self::String(val)
+    }
+}
+
 impl From<String> for Value {
     fn from(val: String) -> Self {
-        Self::String(val)
+        Self::String(val.into())
     }
 }
 
@@ -194,7 +209,7 @@ impl From<bool> for Value {
 
 impl From<u64> for Value {
     fn from(val: u64) -> Self {
-        Value::Number((val as f64).into())
+        Value::Number(val as f64)
     }
 }
 
@@ -226,11 +241,7 @@ impl Into<bool> for Value {
 
 impl Value {
     fn is_String(&self) -> bool {
-        if let Value::String(_) = self {
-            true
-        } else {
-            false
-        }
+        matches!(self, Value::String(_))
     }
 }
 
@@ -254,4 +265,31 @@ impl Value {
         let v2 = rhs.ToNumber(vm)?;
         Ok(v1 - v2)
     }
+    // ES5 11.8.6
+    pub fn bin_instanceof(&self, rhs: Self, vm: &mut VM) -> JSResult {
+        match rhs {
+            Value::Object(ref f) => f.HasInstance(self, vm),
+            _ => Err(vm.ctx.new_Error(
+                error::ErrorKind::TypeError,
+                "Right-hand side of 'instanceof' is not an object",
+            )),
+        }
+    }
+    // ES5 11.8.7
+    pub fn bin_in(&self, rhs: Self, vm: &mut VM) -> JSResult {
+        match rhs {
+            Value::Object(ref o) => {
+                let key = self.ToPropertyKey(vm)?;
+                Ok(o.HasProperty(&key).into())
+            }
+            _ => {
+                let message = format!(
+                    "Cannot use 'in' operator to search for '{}' in {}",
+                    self.ToString(vm)?,
+                    rhs.ToString(vm)?
+                );
+                Err(vm.ctx.new_Error(error::ErrorKind::TypeError, &message))
+            }
+        }
+    }
 }
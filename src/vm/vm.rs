@@ -1,77 +1,162 @@
 use super::context::*;
+use super::options::{Clock, Options, Output, TimeZone};
+use super::random::Random;
 use crate::objects::*;
 use crate::vm::code::*;
+use crate::vm::jsstring::JSString;
+use crate::vm::scope::*;
 use crate::vm::value::*;
 
-use std::collections::HashMap;
 use std::rc::Rc;
 
+const SUPER_NOT_CALLED: &str = "Must call super constructor in derived class before accessing 'this' or returning from derived constructor";
+
 pub struct VM<'a> {
     callstack: Vec<Frame>,
-    global_scope: HashMap<String, Value>,
-    scopes: Vec<HashMap<String, Value>>,
+    global: GcObject,
     pub ctx: &'a Context,
-    thises: Vec<Value>, // Execution contexts
-    throw_stack: Vec<Value>,
+    random: Random,
+    clock: Clock,
+    timezone: TimeZone,
+    output: Output,
 }
 
 struct Frame {
     datastack: Vec<Value>,
     code: Rc<Code>,
     ip: usize,
+    // Unbound in a derived class constructor until super() is called
+    this: Option<Value>,
+    callee: Option<GcObject>,
+    // The constructor new was applied to, in a constructor call
+    new_target: Option<GcObject>,
+    // The parameters of a function call, the first scope of its chain
+    params: Option<GcEnv>,
+    env: Option<GcEnv>,
+}
+
+// Where a name is bound
+enum Reference {
+    Binding(GcEnv),
+    Object(GcObject),
+    Param(GcEnv, usize),
+    Global,
 }
 
 impl Frame {
-    fn new(code: Rc<Code>) -> Self {
+    fn arguments_object(&mut self, ctx: &Context) -> GcObject {
+        let params = self.params.as_ref().expect("arguments outside a function");
+        let args = {
+            let params = params.params().borrow();
+            if let Some(ref arguments) = params.arguments {
+                return arguments.clone();
+            }
+            // Mapped parameters assigned before now hold the current values
+            let mut args = params.args.clone();
+            if params.mapped {
+                for (arg, value) in args.iter_mut().zip(&params.values) {
+                    *arg = value.clone();
+                }
+            }
+            args
+        };
+        let callee = self.callee.clone().expect("arguments outside a function");
+        let arguments = ctx.new_Arguments(args, callee).unwrap_object();
+        params.params().borrow_mut().arguments = Some(arguments.clone());
+        arguments
+    }
+    fn new(code: Rc<Code>, this: Value) -> Self {
         Frame {
             datastack: Vec::new(),
             code,
             ip: 0,
+            this: Some(this),
+            callee: None,
+            new_target: None,
+            params: None,
+            env: None,
         }
     }
 }
 
-fn builtin_print(_vm: &mut VM, arguments: &[Value]) -> JSResult {
-    for arg in arguments {
-        print!("{} ", arg.to_string());
-        // match arg.to_string() {
-        //     Ok(s) => print!("{} ", s),
-        //     Err(msg) => return Err(msg),
-        // }
+// The home object of a method, which super refers to the prototype of
+fn function_home(function: &GcObject) -> Option<GcObject> {
+    match function.borrow().payload {
+        ObjectPayload::Function(ref f) => f.home(),
+        _ => None,
     }
-    println!();
+}
+
+// A value as shown in the message of a TypeError about it
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Undefined => "undefined".to_string(),
+        Value::Null => "null".to_string(),
+        Value::String(s) => format!("\"{}\"", s.to_string_lossy()),
+        Value::Object(o) => format!("#<{}>", o.borrow().Class()),
+        value => value.to_string(),
+    }
+}
+
+fn builtin_print(vm: &mut VM, _this: &Value, arguments: &[Value]) -> JSResult {
+    let line: Vec<String> = arguments.iter().map(|arg| arg.to_string()).collect();
+    (vm.output)(&line.join(" "));
     Ok(Value::default())
 }
 
 impl<'a> VM<'a> {
+    #[allow(dead_code)] // embedder API, the CLI always passes options
     pub fn new(code: Code, ctx: &'a Context) -> Self {
-        let callstack = vec![Frame::new(Rc::from(code))];
+        Self::with_options(code, ctx, Options::default())
+    }
+
+    pub fn with_options(code: Code, ctx: &'a Context, options: Options) -> Self {
+        // The global object, which is also `this` in global code
+        let global = ctx.new_Object(Some(ctx.Object_prototype.clone()));
+        let callstack = vec![Frame::new(Rc::from(code), global.clone())];
         Self::init_vm(VM {
             callstack,
-            global_scope: HashMap::new(),
-            scopes: Vec::new(),
+            global: global.unwrap_object(),
             ctx,
-            thises: vec![Value::default()],
-            throw_stack: Vec::new(),
+            random: options.random,
+            clock: options.clock,
+            timezone: options.timezone,
+            output: options.output,
         })
     }
 
     fn init_vm(vm: Self) -> Self {
-        let mut vm = vm;
-        vm.global_scope
-            .insert("Object".to_string(), vm.ctx.Object_function.clone().into());
-        vm.global_scope.insert(
-            "Function".to_string(),
-            vm.ctx.Function_function.clone().into(),
-        );
-        vm.global_scope
-            .insert("Number".to_string(), vm.ctx.Number_function.clone().into());
-        vm.global_scope
-            .insert("String".to_string(), vm.ctx.String_function.clone().into());
-        vm.global_scope.insert(
-            "print".to_string(),
-            vm.ctx.new_BuiltinFunction("print", builtin_print, 0).into(),
-        );
+        let ctx = vm.ctx;
+        let mut props = global::get_props(ctx);
+        let mut insert = |name: &str, value: Value| {
+            props.insert(name.into(), Property::new(value).dont_enum());
+        };
+        insert("globalThis", vm.global.clone().into());
+        insert("Object", ctx.Object_function.clone().into());
+        insert("Function", ctx.Function_function.clone().into());
+        insert("Array", ctx.Array_function.clone().into());
+        insert("Boolean", ctx.Boolean_function.clone().into());
+        insert("Date", ctx.Date_function.clone().into());
+        insert("Number", ctx.Number_function.clone().into());
+        insert("String", ctx.String_function.clone().into());
+        insert("Math", ctx.Math.clone().into());
+        for kind in error::ErrorKind::ALL.iter() {
+            insert(kind.name(), ctx.Error_function(*kind).clone().into());
+        }
+        insert("print", ctx.new_BuiltinFunction("print", builtin_print, 0));
+        {
+            let mut global = vm.global.borrow_mut();
+            global.props.extend(props);
+            // The var and function names of the script are properties of the
+            // global object before it runs, undefined unless already there
+            // (ES5 10.5)
+            for name in vm.callstack[0].code.vars.iter() {
+                global
+                    .props
+                    .entry(name.as_str().into())
+                    .or_insert_with(|| Property::new(Value::Undefined).dont_delete());
+            }
+        }
         vm
     }
 
@@ -86,8 +171,7 @@ impl<'a> VM<'a> {
         while let Some(frm) = self.callstack.last_mut() {
             let ref instrs = frm.code.instrs;
             if frm.ip >= instrs.len() {
-                self.callstack.pop();
-                return Ok(Value::Undefined);
+                return self.return_from_frame(Value::Undefined);
             }
             let ref consts = frm.code.consts;
             let ref names = frm.code.names;
@@ -99,10 +183,6 @@ impl<'a> VM<'a> {
                 Instruction::LoadBool(b) => {
                     frm.datastack.push(Value::Boolean(*b));
                 }
-                Instruction::PrintTop => match frm.datastack.pop() {
-                    None => panic!("Datastack empty"),
-                    Some(v) => println!("{}", v),
-                },
                 Instruction::BinAdd => match (frm.datastack.pop(), frm.datastack.pop()) {
                     (Some(v2), Some(v1)) => {
                         let res = v1.bin_add(v2, self)?;
@@ -121,6 +201,24 @@ impl<'a> VM<'a> {
                     }
                     _ => panic!("stack underflow during BinOp"),
                 },
+                Instruction::BinInstanceOf => match (frm.datastack.pop(), frm.datastack.pop()) {
+                    (Some(v1), Some(v2)) => {
+                        let res = v2.bin_instanceof(v1, self)?;
+                        if let Some(frm) = self.callstack.last_mut() {
+                            frm.datastack.push(res);
+                        }
+                    }
+                    _ => panic!("stack underflow during BinOp"),
+                },
+                Instruction::BinIn => match (frm.datastack.pop(), frm.datastack.pop()) {
+                    (Some(v1), Some(v2)) => {
+                        let res = v2.bin_in(v1, self)?;
+                        if let Some(frm) = self.callstack.last_mut() {
+                            frm.datastack.push(res);
+                        }
+                    }
+                    _ => panic!("stack underflow during BinOp"),
+                },
                 Instruction::BinEq => match (frm.datastack.pop(), frm.datastack.pop()) {
                     (Some(v1), Some(v2)) => frm.datastack.push(Value::Boolean(v1 == v2)),
                     _ => panic!("stack underflow during BinOp"),
@@ -130,118 +228,187 @@ impl<'a> VM<'a> {
                     _ => panic!("const cannot be indexed"),
                 }),
                 Instruction::Call(nargs) => {
-                    let v = frm.datastack.pop().unwrap();
-                    let arguments = Vec::from(&frm.datastack[frm.datastack.len() - nargs..]);
-                    for _ in 0..*nargs {
-                        frm.datastack.pop().expect("datastack underflow");
-                    }
-                    let res = v.as_object(self.ctx).borrow().Call(self, &arguments[..]);
+                    let arguments = frm.datastack.split_off(frm.datastack.len() - nargs);
+                    let f = frm.datastack.pop().expect("datastack underflow");
+                    let f = self.function(f, "function")?;
+                    let res = f.Call(self, Value::Undefined, &arguments);
                     if let Some(frm) = self.callstack.last_mut() {
                         frm.datastack.push(res?);
-                        // match &res {
-                        //     Ok(val) => frm.datastack.push(val.clone()),
-                        //     Err(_) => return res,
-                        // };
                     };
                 }
-                Instruction::LoadName(idx) => {
-                    let ref name = names[*idx];
-                    let mut found = false;
-                    if let Some(scope) = self.scopes.last_mut() {
-                        if let Some(v) = scope.get(name) {
-                            frm.datastack.push(v.clone());
-                            found = true;
-                        }
+                Instruction::CallWithThis(nargs) => {
+                    let arguments = frm.datastack.split_off(frm.datastack.len() - nargs);
+                    let f = frm.datastack.pop().expect("datastack underflow");
+                    let this = frm.datastack.pop().expect("datastack underflow");
+                    let f = self.function(f, "function")?;
+                    let res = f.Call(self, this, &arguments);
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(res?);
+                    };
+                }
+                Instruction::CallSpread => {
+                    let array = frm.datastack.pop().expect("datastack underflow");
+                    let f = frm.datastack.pop().expect("datastack underflow");
+                    let arguments = self.spread(&array)?;
+                    let f = self.function(f, "function")?;
+                    let res = f.Call(self, Value::Undefined, &arguments);
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(res?);
+                    };
+                }
+                Instruction::CallWithThisSpread => {
+                    let array = frm.datastack.pop().expect("datastack underflow");
+                    let f = frm.datastack.pop().expect("datastack underflow");
+                    let this = frm.datastack.pop().expect("datastack underflow");
+                    let arguments = self.spread(&array)?;
+                    let f = self.function(f, "function")?;
+                    let res = f.Call(self, this, &arguments);
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(res?);
+                    };
+                }
+                Instruction::ImplicitThis(idx) => {
+                    let name = names[*idx].clone();
+                    let this = match self.resolve(&name) {
+                        Reference::Object(object) => object.into(),
+                        _ => Value::Undefined,
+                    };
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(this);
                     }
-                    if !found {
-                        if let Some(v) = self.global_scope.get(name) {
-                            frm.datastack.push(v.clone());
-                        } else {
-                            panic!("NameError: '{}' not found", name);
+                }
+                Instruction::LoadName(idx) => {
+                    let name = names[*idx].clone();
+                    let v = match self.resolve(&name) {
+                        Reference::Binding(env) => match env.binding(&name) {
+                            Some(Binding {
+                                value: Some(ref v), ..
+                            }) => v.clone(),
+                            _ => return Err(self.uninitialized_error(&name)),
+                        },
+                        Reference::Object(object) => object.Get(&name.as_str().into(), self)?,
+                        Reference::Param(env, idx) => self.load_param(&env, idx)?,
+                        Reference::Global => {
+                            let global = self.global.clone();
+                            if !global.HasProperty(&name.as_str().into()) {
+                                return Err(self.ctx.new_Error(
+                                    error::ErrorKind::ReferenceError,
+                                    &format!("{} is not defined", name),
+                                ));
+                            }
+                            global.Get(&name.into(), self)?
                         }
+                    };
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(v);
                     }
                 }
                 Instruction::StoreName(idx) => {
-                    let ref name = frm.code.names[*idx];
+                    let name = frm.code.names[*idx].clone();
                     let v = frm.datastack.pop().unwrap();
-                    if let Some(scope) = self.scopes.last_mut() {
-                        scope.insert(name.clone(), v);
-                    } else {
-                        self.global_scope.insert(name.clone(), v);
+                    match self.resolve(&name) {
+                        Reference::Binding(env) => match env.binding(&name) {
+                            Some(Binding { value: None, .. }) => {
+                                return Err(self.uninitialized_error(&name))
+                            }
+                            Some(Binding { mutable: false, .. }) => {
+                                return Err(self.ctx.new_Error(
+                                    error::ErrorKind::TypeError,
+                                    "Assignment to constant variable.",
+                                ))
+                            }
+                            _ => env.set_binding(&name, v),
+                        },
+                        Reference::Object(object) => {
+                            object.Put(name.into(), v, self)?;
+                        }
+                        Reference::Param(env, idx) => self.store_param(&env, idx, v)?,
+                        Reference::Global => {
+                            let global = self.global.clone();
+                            global.Put(name.into(), v, self)?;
+                        }
                     }
                 }
                 Instruction::LoadArg(idx) => {
-                    frm.datastack.push(frm.datastack[*idx].clone());
+                    let idx = *idx;
+                    let params = frm.params.clone().expect("parameters outside a function");
+                    let v = self.load_param(&params, idx)?;
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(v);
+                    }
+                }
+                Instruction::StoreArg(idx) => {
+                    let idx = *idx;
+                    let params = frm.params.clone().expect("parameters outside a function");
+                    let v = frm.datastack.pop().expect("datastack underflow");
+                    self.store_param(&params, idx, v)?;
+                }
+                Instruction::LoadArguments => {
+                    let arguments = frm.arguments_object(self.ctx);
+                    frm.datastack.push(arguments.into());
                 }
                 Instruction::New(nargs) => {
+                    let args = frm.datastack.split_off(frm.datastack.len() - nargs);
                     let f = frm.datastack.pop().expect("datastack underflow");
-                    let args = Vec::from(&frm.datastack[frm.datastack.len() - nargs..]);
-                    for _ in 0..*nargs {
-                        frm.datastack.pop().expect("data stack underflow");
-                        // args.push (frm.datastack.pop().expect ("data stack underflow").clone ());
-                    }
-                    let res = f.as_object(self.ctx).borrow().Construct(self, &args);
+                    let f = self.function(f, "constructor")?;
+                    let res = f.Construct(self, &args);
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(res?);
+                    };
+                }
+                Instruction::NewSpread => {
+                    let array = frm.datastack.pop().expect("datastack underflow");
+                    let f = frm.datastack.pop().expect("datastack underflow");
+                    let args = self.spread(&array)?;
+                    let f = self.function(f, "constructor")?;
+                    let res = f.Construct(self, &args);
                     if let Some(frm) = self.callstack.last_mut() {
                         frm.datastack.push(res?);
-                        // match &res {
-                        //     Ok(val) => frm.datastack.push(val.clone()),
-                        //     Err(_) => return res,
-                        // }
                     };
                 }
                 Instruction::LoadProperty => {
-                    let prop = frm
-                        .datastack
-                        .pop()
-                        .expect("data stack underflow")
-                        .to_string();
-                    let v: Value = frm
-                        .datastack
-                        .pop()
-                        .expect("data stack underflow")
-                        .as_object(self.ctx)
-                        .into();
-                    self.thises.push(v.clone());
-                    frm.datastack.push(v.unwrap_object().borrow().Get(&prop));
-                    self.thises.pop();
+                    let prop = frm.datastack.pop().expect("data stack underflow");
+                    let object = frm.datastack.pop().expect("data stack underflow");
+                    if let Value::Undefined | Value::Null = object {
+                        let message = format!(
+                            "Cannot read properties of {} (reading '{}')",
+                            describe(&object),
+                            prop
+                        );
+                        return Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message));
+                    }
+                    let prop = prop.ToPropertyKey(self)?;
+                    let v = object.as_object(self.ctx).Get(&prop, self)?;
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(v);
+                    }
                 }
                 Instruction::StoreProperty => {
-                    let prop = frm
-                        .datastack
-                        .pop()
-                        .expect("data stack underflow")
-                        .to_string();
+                    let prop = frm.datastack.pop().expect("data stack underflow");
                     let lhs = frm.datastack.pop().expect("data stack underflow");
                     let rhs = frm.datastack.pop().expect("data stack underflow");
-                    lhs.as_object(self.ctx).borrow_mut().Put(prop, rhs);
-                }
-                Instruction::LoadThis => {
-                    frm.datastack.push(match self.thises.last() {
-                        Some(v) => v.clone(),
-                        None => panic!("failed to load this"),
-                    });
+                    if let Value::Undefined | Value::Null = lhs {
+                        let message = format!(
+                            "Cannot set properties of {} (setting '{}')",
+                            describe(&lhs),
+                            prop
+                        );
+                        return Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message));
+                    }
+                    let prop = prop.ToPropertyKey(self)?;
+                    lhs.as_object(self.ctx).Put(prop, rhs, self)?;
                 }
+                Instruction::LoadThis => match frm.this {
+                    Some(ref this) => frm.datastack.push(this.clone()),
+                    None => {
+                        return Err(self
+                            .ctx
+                            .new_Error(error::ErrorKind::ReferenceError, SUPER_NOT_CALLED))
+                    }
+                },
                 Instruction::Return => {
                     let v = frm.datastack.pop().expect("datastack underflow");
-                    self.callstack.pop();
-                    return Ok(v);
-                }
-                Instruction::Throw => {
-                    let v = frm.datastack.pop().expect("datastack underflow");
-                    self.throw_stack.push(v.clone());
-                    return Err(v);
-                }
-                Instruction::PushThis => {
-                    let v = frm
-                        .datastack
-                        .last()
-                        .expect("data stack underflow")
-                        .as_object(self.ctx)
-                        .into();
-                    self.thises.push(v);
-                }
-                Instruction::PopThis => {
-                    self.thises.pop().expect("'this' underflow");
+                    return self.return_from_frame(v);
                 }
                 Instruction::PopJumpIfFalse(delta) => {
                     let condition = frm.datastack.pop().expect("datastack underflow");
@@ -250,45 +417,608 @@ impl<'a> VM<'a> {
                         frm.ip = *delta;
                     }
                 }
+                Instruction::PopJumpIfDefined(delta) => {
+                    match frm.datastack.pop().expect("datastack underflow") {
+                        Value::Undefined => (),
+                        _ => frm.ip = *delta,
+                    }
+                }
                 Instruction::Jump(delta) => {
                     frm.ip = *delta;
                 }
-                Instruction::MakeArray(_len) => {
-                    panic!("Unimplemented")
-                    // let els = Vec::from(frm.datastack[frm.datastack.len() - len..].to_vec());
-                    // frm.datastack.drain(frm.datastack.len() - len..);
-                    // frm.datastack.push(Value::new_arrayobject(self.ctx, els));
+                Instruction::Pop => {
+                    frm.datastack.pop().expect("datastack underflow");
+                }
+                Instruction::Dup => {
+                    let v = frm.datastack.last().expect("datastack underflow").clone();
+                    frm.datastack.push(v);
+                }
+                Instruction::JumpIfCase(delta) => {
+                    let test = frm.datastack.pop().expect("datastack underflow");
+                    let discriminant = frm.datastack.last().expect("datastack underflow");
+                    if discriminant.StrictEquals(&test) {
+                        frm.datastack.pop();
+                        frm.ip = *delta;
+                    }
+                }
+                Instruction::JumpTable(idx) => {
+                    let discriminant = frm.datastack.pop().expect("datastack underflow");
+                    frm.ip = frm.code.tables[*idx].target(&discriminant);
+                }
+                Instruction::MakeArray(len) => {
+                    let els = frm.datastack.split_off(frm.datastack.len() - len);
+                    frm.datastack.push(self.ctx.new_Array(els));
+                }
+                Instruction::ArrayPush | Instruction::ArraySpread => {
+                    let values = match instr {
+                        Instruction::ArrayPush => {
+                            vec![frm.datastack.pop().expect("datastack underflow")]
+                        }
+                        _ => {
+                            let iterable = frm.datastack.pop().expect("datastack underflow");
+                            self.spread(&iterable)?
+                        }
+                    };
+                    let array = self
+                        .callstack
+                        .last()
+                        .expect("callstack underflow")
+                        .datastack
+                        .last()
+                        .expect("datastack underflow")
+                        .unwrap_object();
+                    for value in values {
+                        let length = array.Get(&"length".into(), self)?;
+                        array.Put(length.to_string().into(), value, self)?;
+                    }
+                }
+                Instruction::ToString => {
+                    let v = frm.datastack.pop().expect("datastack underflow");
+                    let s = v.ToString(self)?;
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(s);
+                    }
+                }
+                Instruction::ToArray => {
+                    let iterable = frm.datastack.pop().expect("datastack underflow");
+                    let elements = self.spread(&iterable)?;
+                    let array = self.ctx.new_Array(elements);
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(array);
+                    }
+                }
+                Instruction::ArrayRest(idx) => {
+                    let idx = *idx;
+                    let array = frm.datastack.last().expect("datastack underflow").clone();
+                    let elements = self.spread(&array)?;
+                    let rest = self
+                        .ctx
+                        .new_Array(elements.get(idx..).unwrap_or(&[]).to_vec());
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(rest);
+                    }
+                }
+                Instruction::CheckObjectCoercible => {
+                    let name = match frm.datastack.last().expect("datastack underflow") {
+                        Value::Undefined => "undefined",
+                        Value::Null => "null",
+                        _ => continue,
+                    };
+                    let message = format!("Cannot destructure '{0}' as it is {0}.", name);
+                    return Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message));
+                }
+                Instruction::LoadDestructured => {
+                    let key = frm.datastack.pop().expect("datastack underflow");
+                    let len = frm.datastack.len();
+                    let keys = frm.datastack[len - 1].unwrap_object();
+                    let object = frm.datastack[len - 2].as_object(self.ctx);
+                    let key = key.ToPropertyKey(self)?;
+                    let length = keys.Get(&"length".into(), self)?;
+                    keys.Put(length.to_string().into(), key.clone().into(), self)?;
+                    let v = object.Get(&key, self)?;
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(v);
+                    }
+                }
+                Instruction::ObjectRest => {
+                    let keys = frm.datastack.pop().expect("datastack underflow");
+                    let object = frm
+                        .datastack
+                        .pop()
+                        .expect("datastack underflow")
+                        .as_object(self.ctx);
+                    let keys: Vec<JSString> = self
+                        .spread(&keys)?
+                        .iter()
+                        .map(|k| k.unwrap_string().clone())
+                        .collect();
+                    let rest = self
+                        .ctx
+                        .new_Object(Some(self.ctx.Object_prototype.clone()))
+                        .unwrap_object();
+                    for (key, value) in object::enumerable_own_properties(self, &object)? {
+                        if !keys.contains(&key) {
+                            rest.borrow_mut().PutValue(key, value);
+                        }
+                    }
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(rest.into());
+                    }
+                }
+                Instruction::ForInKeys => {
+                    let keys = match frm.datastack.pop().expect("datastack underflow") {
+                        Value::Undefined | Value::Null => Vec::new(),
+                        ref v => object::for_in_keys(&v.as_object(self.ctx)),
+                    };
+                    frm.datastack.push(self.ctx.new_Array(keys));
+                }
+                Instruction::IterNext(exit) => {
+                    let exit = *exit;
+                    let len = frm.datastack.len();
+                    let idx = match frm.datastack[len - 1] {
+                        Value::Number(n) => n as usize,
+                        _ => unreachable!("Not an iteration index"),
+                    };
+                    let array = frm.datastack[len - 2].unwrap_object();
+                    let length = match array.Get(&"length".into(), self)? {
+                        Value::Number(n) => n as usize,
+                        _ => 0,
+                    };
+                    if idx >= length {
+                        if let Some(frm) = self.callstack.last_mut() {
+                            frm.ip = exit;
+                        }
+                    } else {
+                        let element = array.Get(&idx.to_string().into(), self)?;
+                        if let Some(frm) = self.callstack.last_mut() {
+                            frm.datastack[len - 1] = Value::Number((idx + 1) as f64);
+                            frm.datastack.push(element);
+                        }
+                    }
+                }
+                Instruction::EnterWith => {
+                    let object = match frm.datastack.pop().expect("datastack underflow") {
+                        Value::Undefined | Value::Null => {
+                            return Err(self.ctx.new_Error(
+                                error::ErrorKind::TypeError,
+                                "Cannot convert undefined or null to object",
+                            ))
+                        }
+                        ref v => v.as_object(self.ctx),
+                    };
+                    frm.env = Some(Environment::object(object, frm.env.take()));
+                }
+                Instruction::PushScope(idx) => {
+                    let names = &frm.code.blocks[*idx];
+                    frm.env = Some(Environment::declarative(names, frm.env.take()));
+                }
+                Instruction::PushVarScope => {
+                    // A var named like a parameter starts with its value
+                    let params = frm.params.clone().expect("not a function frame");
+                    let params = params.params().borrow();
+                    let vars = frm
+                        .code
+                        .vars
+                        .iter()
+                        .map(|name| {
+                            let value = match frm.code.params.iter().position(|p| p == name) {
+                                Some(idx) => params.values[idx].clone(),
+                                None => Value::Undefined,
+                            };
+                            (name.clone(), value)
+                        })
+                        .collect();
+                    frm.env = Some(Environment::variables(vars, frm.env.take()));
+                }
+                Instruction::CopyScope => {
+                    let env = frm.env.as_ref().expect("scope underflow").copy();
+                    frm.env = Some(env);
+                }
+                Instruction::PopScope => {
+                    let env = frm.env.take().expect("scope underflow");
+                    frm.env = env.parent.clone();
+                }
+                Instruction::InitBinding(idx) => {
+                    let name = &names[*idx];
+                    let v = frm.datastack.pop().expect("datastack underflow");
+                    let mut env = frm.env.clone();
+                    while let Some(scope) = env {
+                        if scope.binding(name).is_some() {
+                            scope.set_binding(name, v);
+                            break;
+                        }
+                        env = scope.parent.clone();
+                    }
+                }
+                Instruction::MakeClosure(idx) => {
+                    let template = consts[*idx].unwrap_object();
+                    let closure = self.ctx.new_Closure(&template, frm.env.clone());
+                    frm.datastack.push(closure);
+                }
+                Instruction::MakeArrow(idx, captures_arguments) => {
+                    let template = consts[*idx].unwrap_object();
+                    let this = frm.this.clone().unwrap_or_default();
+                    let mut bindings = vec![("this".to_string(), this)];
+                    // Global code has no arguments object
+                    if *captures_arguments && frm.callee.is_some() {
+                        let arguments = frm.arguments_object(self.ctx);
+                        bindings.push(("arguments".to_string(), arguments.into()));
+                    }
+                    let names: Vec<_> = bindings
+                        .iter()
+                        .map(|(name, _)| (name.clone(), false))
+                        .collect();
+                    let env = Environment::declarative(&names, frm.env.clone());
+                    for (name, value) in bindings {
+                        env.set_binding(&name, value);
+                    }
+                    let closure = self.ctx.new_Closure(&template, Some(env));
+                    // Arrow functions in a method refer to its super too
+                    if let Some(home) = frm.callee.as_ref().and_then(function_home) {
+                        if let ObjectPayload::Function(ref mut f) =
+                            closure.unwrap_object().borrow_mut().payload
+                        {
+                            f.set_home(home);
+                        }
+                    }
+                    frm.datastack.push(closure);
+                }
+                Instruction::MakeObject => {
+                    let object = self.ctx.new_Object(Some(self.ctx.Object_prototype.clone()));
+                    frm.datastack.push(object);
+                }
+                Instruction::InitProperty | Instruction::InitGetter | Instruction::InitSetter => {
+                    let value = frm.datastack.pop().expect("datastack underflow");
+                    let key = frm.datastack.pop().expect("datastack underflow");
+                    let key = key.unwrap_string().clone();
+                    let object = frm.datastack.last().expect("datastack underflow");
+                    let object = object.unwrap_object();
+                    let mut object = object.borrow_mut();
+                    match instr {
+                        Instruction::InitGetter => object.PutAccessor(key, Some(value), None),
+                        Instruction::InitSetter => object.PutAccessor(key, None, Some(value)),
+                        _ => {
+                            object.props.insert(key, Property::new(value));
+                        }
+                    }
+                }
+                Instruction::MakeClass(idx, extends) => {
+                    let template = consts[*idx].unwrap_object();
+                    let env = frm.env.clone();
+                    let parent = match extends {
+                        true => Some(frm.datastack.pop().expect("datastack underflow")),
+                        false => None,
+                    };
+                    let (constructor_parent, prototype_parent) = match parent {
+                        None => (
+                            self.ctx.Function_prototype.clone(),
+                            Some(self.ctx.Object_prototype.clone()),
+                        ),
+                        Some(Value::Null) => (self.ctx.Function_prototype.clone(), None),
+                        Some(Value::Object(ref o))
+                            if matches!(o.borrow().payload, ObjectPayload::Function(_)) =>
+                        {
+                            match o.Get(&"prototype".into(), self)? {
+                                Value::Object(ref prototype) => {
+                                    (o.clone(), Some(prototype.clone()))
+                                }
+                                Value::Null => (o.clone(), None),
+                                ref other => {
+                                    let message = format!(
+                                        "Class extends value does not have valid prototype property {}",
+                                        other.ToString(self)?
+                                    );
+                                    return Err(self
+                                        .ctx
+                                        .new_Error(error::ErrorKind::TypeError, &message));
+                                }
+                            }
+                        }
+                        Some(ref other) => {
+                            let message = format!(
+                                "Class extends value {} is not a constructor or null",
+                                other.ToString(self)?
+                            );
+                            return Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message));
+                        }
+                    };
+                    let constructor = self.ctx.new_Closure(&template, env).unwrap_object();
+                    let prototype = constructor.Get(&"prototype".into(), self)?;
+                    let prototype = prototype.unwrap_object();
+                    {
+                        let mut prototype = prototype.borrow_mut();
+                        prototype.__proto__ = prototype_parent;
+                        let value = Property::new(constructor.clone().into()).dont_enum();
+                        prototype.props.insert("constructor".into(), value);
+                    }
+                    let mut object = constructor.borrow_mut();
+                    object.__proto__ = Some(constructor_parent);
+                    if let ObjectPayload::Function(ref mut f) = object.payload {
+                        f.set_home(prototype.clone());
+                    }
+                    drop(object);
+                    let frm = self.callstack.last_mut().expect("callstack underflow");
+                    frm.datastack.push(constructor.into());
+                    frm.datastack.push(prototype.into());
+                }
+                Instruction::ClassMethod(is_static)
+                | Instruction::ClassGetter(is_static)
+                | Instruction::ClassSetter(is_static) => {
+                    let value = frm.datastack.pop().expect("datastack underflow");
+                    let key = frm.datastack.pop().expect("datastack underflow");
+                    let key = key.unwrap_string().clone();
+                    let len = frm.datastack.len();
+                    let object = match is_static {
+                        true => frm.datastack[len - 2].unwrap_object(),
+                        false => frm.datastack[len - 1].unwrap_object(),
+                    };
+                    if let ObjectPayload::Function(ref mut f) =
+                        value.unwrap_object().borrow_mut().payload
+                    {
+                        f.set_home(object.clone());
+                    }
+                    let mut object = object.borrow_mut();
+                    match instr {
+                        Instruction::ClassGetter(_) => {
+                            object.PutAccessor(key.clone(), Some(value), None)
+                        }
+                        Instruction::ClassSetter(_) => {
+                            object.PutAccessor(key.clone(), None, Some(value))
+                        }
+                        _ => {
+                            object.props.insert(key.clone(), Property::new(value));
+                        }
+                    }
+                    // Class members aren't enumerable
+                    if let Some(prop) = object.props.get_mut(&key) {
+                        *prop = prop.clone().dont_enum();
+                    }
+                }
+                Instruction::SuperCall(_) | Instruction::SuperCallSpread => {
+                    let args = match instr {
+                        Instruction::SuperCall(nargs) => {
+                            let len = frm.datastack.len();
+                            frm.datastack.split_off(len - nargs)
+                        }
+                        _ => {
+                            let array = frm.datastack.pop().expect("datastack underflow");
+                            self.spread(&array)?
+                        }
+                    };
+                    let frm = self.callstack.last().expect("callstack underflow");
+                    let (callee, new_target) = match (&frm.callee, &frm.new_target) {
+                        (Some(callee), Some(new_target)) => (callee.clone(), new_target.clone()),
+                        _ => {
+                            return Err(self.ctx.new_Error(
+                                error::ErrorKind::SyntaxError,
+                                "'super' keyword unexpected here",
+                            ))
+                        }
+                    };
+                    let parent = callee.borrow().__proto__.clone();
+                    let function = match parent {
+                        Some(ref parent) => match parent.borrow().payload {
+                            ObjectPayload::Function(ref f) => Some(f.clone()),
+                            _ => None,
+                        },
+                        None => None,
+                    };
+                    let this = match (function, parent) {
+                        (Some(function), Some(parent)) => {
+                            function.Construct(&parent, self, &args, &new_target)?
+                        }
+                        _ => {
+                            return Err(self.ctx.new_Error(
+                                error::ErrorKind::TypeError,
+                                "Super constructor is not a constructor",
+                            ))
+                        }
+                    };
+                    let frm = self.callstack.last_mut().expect("callstack underflow");
+                    if frm.this.is_some() {
+                        return Err(self.ctx.new_Error(
+                            error::ErrorKind::ReferenceError,
+                            "Super constructor may only be called once",
+                        ));
+                    }
+                    frm.this = Some(this.clone());
+                    frm.datastack.push(this.clone());
+                }
+                Instruction::LoadSuperProperty => {
+                    let key = frm.datastack.pop().expect("datastack underflow");
+                    let home = match frm.callee.as_ref().and_then(function_home) {
+                        Some(home) => home,
+                        None => {
+                            return Err(self.ctx.new_Error(
+                                error::ErrorKind::SyntaxError,
+                                "'super' keyword unexpected here",
+                            ))
+                        }
+                    };
+                    let this = frm.datastack.pop().expect("datastack underflow");
+                    let key = key.ToPropertyKey(self)?;
+                    let proto = home.borrow().__proto__.clone();
+                    let v = match proto {
+                        Some(proto) => proto.GetWithReceiver(&key, this, self)?,
+                        None => Value::Undefined,
+                    };
+                    if let Some(frm) = self.callstack.last_mut() {
+                        frm.datastack.push(v);
+                    }
                 }
             }
         }
         Ok(Value::Undefined) // Default return value of a frame
     }
 
-    pub fn call_code(&mut self, code: Rc<Code>, arity: usize, args: &[Value]) -> JSResult {
-        let mut frm = Frame::new(code);
-        for arg in args {
-            frm.datastack.push(arg.clone());
-        }
-        for _ in args.len()..arity {
-            frm.datastack.push(Value::Undefined);
+    pub fn call_code(
+        &mut self,
+        code: Rc<Code>,
+        env: Option<GcEnv>,
+        args: &[Value],
+        callee: &GcObject,
+        this: Option<Value>,
+        new_target: Option<GcObject>,
+    ) -> JSResult {
+        // Strict code gets an unmapped arguments object (ES5 10.6)
+        let mapped = code.simple_params && !code.strict;
+        let params = Environment::parameters(code.params.clone(), args, mapped, env);
+        // The rest parameter collects the remaining arguments
+        if let Some(idx) = code.rest {
+            let rest = self.ctx.new_Array(args.get(idx..).unwrap_or(&[]).to_vec());
+            params.params().borrow_mut().values[idx] = rest;
         }
+        let mut frm = Frame::new(code, Value::Undefined);
+        frm.this = this;
+        frm.new_target = new_target;
+        frm.callee = Some(callee.clone());
+        frm.params = Some(params.clone());
+        frm.env = Some(params);
         self.callstack.push(frm);
-        let res = self.exec_top_frame();
-        res
+        self.exec_top_frame()
     }
-    pub fn push_this(&mut self, this: Value) {
-        self.thises.push(this);
+    // The elements of a spread iterable: those of an array or arguments
+    // object, or the code points of a string
+    fn spread(&mut self, value: &Value) -> Result<Vec<Value>, Value> {
+        match value {
+            Value::String(s) => {
+                let units = s.units();
+                let mut elements = Vec::new();
+                let mut i = 0;
+                while i < units.len() {
+                    let pair = (0xD800..0xDC00).contains(&units[i])
+                        && units
+                            .get(i + 1)
+                            .is_some_and(|u| (0xDC00..0xE000).contains(u));
+                    let len = if pair { 2 } else { 1 };
+                    elements.push(JSString::from(units[i..i + len].to_vec()).into());
+                    i += len;
+                }
+                Ok(elements)
+            }
+            Value::Object(o)
+                if matches!(
+                    o.borrow().payload,
+                    ObjectPayload::Array(_) | ObjectPayload::Arguments(_)
+                ) =>
+            {
+                let length = match o.Get(&"length".into(), self)? {
+                    Value::Number(n) if n > 0. => n as usize,
+                    _ => 0,
+                };
+                (0..length)
+                    .map(|k| o.Get(&k.to_string().into(), self))
+                    .collect()
+            }
+            _ => {
+                let message = format!("{} is not iterable", value.ToString(self)?);
+                Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message))
+            }
+        }
     }
-    pub fn pop_this(&mut self) -> Value {
-        match self.thises.pop() {
-            Some(v) => v.clone(),
-            None => panic!("this underflow"),
+    // Parameters with a passed argument live in the arguments object once it
+    // exists
+    fn load_param(&mut self, env: &GcEnv, idx: usize) -> JSResult {
+        let (arguments, value) = {
+            let params = env.params().borrow();
+            (
+                params
+                    .arguments
+                    .clone()
+                    .filter(|_| params.mapped && idx < params.args.len()),
+                params.values[idx].clone(),
+            )
+        };
+        match arguments {
+            Some(arguments) => arguments.Get(&idx.to_string().into(), self),
+            None => Ok(value),
         }
     }
-    pub fn get_this(&self) -> &Value {
-        match self.thises.last() {
-            Some(v) => v,
-            None => panic!("Reference to 'this' does not exist"),
+    fn store_param(&mut self, env: &GcEnv, idx: usize, v: Value) -> Result<(), Value> {
+        let arguments = {
+            let params = env.params().borrow();
+            params
+                .arguments
+                .clone()
+                .filter(|_| params.mapped && idx < params.args.len())
+        };
+        match arguments {
+            Some(arguments) => {
+                arguments.Put(idx.to_string().into(), v, self)?;
+            }
+            None => env.params().borrow_mut().values[idx] = v,
         }
+        Ok(())
+    }
+    // Resolves a name from the innermost scope outwards
+    fn resolve(&self, name: &String) -> Reference {
+        let frm = self.callstack.last().expect("callstack underflow");
+        let mut env = frm.env.clone();
+        loop {
+            let scope = match env {
+                Some(scope) => scope,
+                None => return Reference::Global,
+            };
+            match scope.record {
+                Record::Declarative(ref bindings) if bindings.borrow().contains_key(name) => {
+                    return Reference::Binding(scope.clone())
+                }
+                Record::Object(ref object) if object.HasProperty(&name.as_str().into()) => {
+                    return Reference::Object(object.clone())
+                }
+                Record::Parameters(ref params) => {
+                    if let Some(idx) = params.borrow().names.iter().position(|p| p == name) {
+                        return Reference::Param(scope.clone(), idx);
+                    }
+                    env = scope.parent.clone();
+                }
+                _ => env = scope.parent.clone(),
+            }
+        }
+    }
+    // Pops the frame returning `value`. A constructor call returns its this
+    // value instead, unless the value is an object (ES6 9.2.2).
+    fn return_from_frame(&mut self, value: Value) -> JSResult {
+        let frm = self.callstack.pop().expect("callstack underflow");
+        if frm.new_target.is_none() || matches!(value, Value::Object(_)) {
+            return Ok(value);
+        }
+        match frm.this {
+            Some(this) => Ok(this),
+            None => Err(self
+                .ctx
+                .new_Error(error::ErrorKind::ReferenceError, SUPER_NOT_CALLED)),
+        }
+    }
+    // The function object a value to call or construct must be, or a
+    // TypeError saying it is not a `what`
+    fn function(&self, value: Value, what: &str) -> Result<GcObject, Value> {
+        match value {
+            Value::Object(ref o) if matches!(o.borrow().payload, ObjectPayload::Function(_)) => {
+                Ok(o.clone())
+            }
+            ref value => {
+                let message = format!("{} is not a {}", describe(value), what);
+                Err(self.ctx.new_Error(error::ErrorKind::TypeError, &message))
+            }
+        }
+    }
+    fn uninitialized_error(&self, name: &str) -> Value {
+        self.ctx.new_Error(
+            error::ErrorKind::ReferenceError,
+            &format!("Cannot access '{}' before initialization", name),
+        )
+    }
+    pub fn global(&self) -> &GcObject {
+        &self.global
+    }
+    pub fn random(&mut self) -> f64 {
+        self.random.next_f64()
+    }
+    pub fn now(&self) -> f64 {
+        (self.clock)()
+    }
+    pub fn local_offset(&self, t: f64) -> f64 {
+        (self.timezone)(t)
     }
 }